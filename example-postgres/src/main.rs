@@ -21,7 +21,8 @@ struct User {
 
     // generates `User::by_email(&str) -> Result<Option<Self>>`
     // unlike `#[ormx(get_one = .. )]`, `by_email` will return `None` instead of an error if no record is found.
-    #[ormx(get_optional(&str))]
+    // `#[ormx(sortable)]` adds `UserSort::EmailAsc` and `UserSort::EmailDesc` for `User::stream_all_sorted`
+    #[ormx(get_optional(&str), sortable)]
     email: String,
 
     // custom types need to be annotated with `#[ormx(custom_type)]`
@@ -42,7 +43,7 @@ struct User {
     favourite_color: Option<Color>,
 
     // generates `User::set_last_login(&mut self, Option<NaiveDateTime>) -> Result`
-    #[ormx(default, set)]
+    #[ormx(default, set, sortable)]
     last_login: Option<NaiveDateTime>,
 }
 
@@ -173,6 +174,11 @@ async fn main() -> anyhow::Result<()> {
         .await?;
 
 
+    info!("load users in an order chosen at runtime..");
+    let recent = User::all_sorted(&mut *tx, UserSort::LastLoginDesc, 0, 10).await?;
+    info!("loaded {} users", recent.len());
+
+
     info!("delete the user from the database..");
     new.delete(&mut *tx).await?;
 
@@ -1,12 +1,17 @@
+use proc_macro2::Span;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    Attribute, Ident, Path, Result, Token, Type,
+    Attribute, Ident, LitStr, Path, Result, Token, Type,
 };
 
+use crate::utils::set_once;
+
 pub enum TableAttr {
     // table = <string>
     Table(String),
+    // schema = <string>
+    Schema(LitStr),
     // id = <ident>
     Id(Ident),
     // insertable [= [<attribute>]* <ident>]?
@@ -15,6 +20,20 @@ pub enum TableAttr {
     Deletable(()),
     // order_by = <string>
     OrderBy(String),
+    // ddl
+    Ddl(()),
+    // unchecked
+    Unchecked(()),
+    // cache
+    Cache(()),
+    // lock
+    Lock(()),
+    // queue(status = <ident>, run_at = <ident> [, attempts = <ident>]?), used by `derive(Queue)`
+    Queue(Queue),
+    // notify [= <string>]?
+    Notify(Option<String>),
+    // backend = <ident>, already read by `BackendKind::select` before parsing
+    Backend(#[allow(dead_code)] Ident),
 }
 
 pub struct Insertable {
@@ -37,10 +56,30 @@ pub enum TableFieldAttr {
     GetMany(Getter),
     // set [= <ident>]?
     Set(Option<Ident>),
+    // counter
+    Counter(()),
     // by_ref
     ByRef(()),
+    // sortable
+    Sortable(()),
+    // sql_type = <string>
+    SqlType(String),
+    // sql_default = <string>
+    SqlDefault(String),
+    // unique
+    Unique(()),
+    // index
+    Index(()),
+    // references = <string>
+    References(String),
+    // generate = <string>
+    Generate(LitStr),
+    // lookup = <ident>
+    Lookup(Ident),
     // insert_attribute = <attribute>
     InsertAttr(AnyAttribute),
+    // skip
+    Skip(()),
 }
 
 #[derive(Clone)]
@@ -52,8 +91,14 @@ pub struct Getter {
 pub enum PatchAttr {
     // table = <string>
     TableName(String),
+    // schema = <string>
+    Schema(LitStr),
     Table(Path),
     Id(String),
+    // unchecked
+    Unchecked(()),
+    // backend = <ident>, already read by `BackendKind::select` before parsing
+    Backend(#[allow(dead_code)] Ident),
 }
 
 pub enum PatchFieldAttr {
@@ -63,6 +108,65 @@ pub enum PatchFieldAttr {
     ByRef(()),
 }
 
+pub enum ProjectionAttr {
+    // table = <path>
+    Table(Path),
+}
+
+pub enum ViewAttr {
+    // view = <string>
+    View(String),
+    // schema = <string>
+    Schema(LitStr),
+    // order_by = <string>
+    OrderBy(String),
+    // materialized
+    Materialized(()),
+    // unchecked
+    Unchecked(()),
+    // backend = <ident>, already read by `BackendKind::select` before parsing
+    Backend(#[allow(dead_code)] Ident),
+}
+
+pub enum JoinedAttr {
+    // from = <path>
+    From(Path),
+    // join(<path>, on = <string>)
+    Join(Join),
+    // order_by = <string>
+    OrderBy(String),
+}
+
+/// Columns of a table used as a queue by `derive(Queue)`
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub struct Queue {
+    pub status: Ident,
+    pub run_at: Ident,
+    /// Defaults to a field named `attempts`
+    pub attempts: Option<Ident>,
+}
+
+enum QueueAttr {
+    // status = <ident>
+    Status(Ident),
+    // run_at = <ident>
+    RunAt(Ident),
+    // attempts = <ident>
+    Attempts(Ident),
+}
+
+pub struct Join {
+    pub table: Path,
+    pub on: LitStr,
+}
+
+pub enum JoinedFieldAttr {
+    // table = <path>
+    Table(Path),
+    // field = <ident>
+    Field(Ident),
+}
+
 impl Parse for Getter {
     fn parse(input: ParseStream) -> Result<Self> {
         let func = if input.peek(syn::token::Eq) {
@@ -82,6 +186,47 @@ impl Parse for Getter {
     }
 }
 
+impl Parse for Join {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        let table = content.parse()?;
+        content.parse::<Token![,]>()?;
+        let on = content.parse::<Ident>()?;
+        if on != "on" {
+            return Err(syn::Error::new(on.span(), "expected `on = \"..\"`"));
+        }
+        content.parse::<Token![=]>()?;
+        Ok(Join {
+            table,
+            on: content.parse()?,
+        })
+    }
+}
+
+impl Parse for Queue {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        let paren = syn::parenthesized!(content in input);
+        let attrs = Punctuated::<WithSpan<QueueAttr>, Token![,]>::parse_terminated(&content)?;
+
+        let (mut status, mut run_at, mut attempts) = (None, None, None);
+        for WithSpan(span, attr) in attrs {
+            match attr {
+                QueueAttr::Status(x) => set_once(&mut status, x, span)?,
+                QueueAttr::RunAt(x) => set_once(&mut run_at, x, span)?,
+                QueueAttr::Attempts(x) => set_once(&mut attempts, x, span)?,
+            }
+        }
+        let missing = |attr| syn::Error::new(paren.span.join(), format!("missing `{attr} = ..`"));
+        Ok(Queue {
+            status: status.ok_or_else(|| missing("status"))?,
+            run_at: run_at.ok_or_else(|| missing("run_at"))?,
+            attempts,
+        })
+    }
+}
+
 impl Parse for Insertable {
     fn parse(input: ParseStream) -> Result<Self> {
         Ok(Self {
@@ -91,18 +236,28 @@ impl Parse for Insertable {
     }
 }
 
-pub fn parse_attrs<A: Parse>(attrs: &[Attribute]) -> Result<Vec<A>> {
+/// Parses all `#[ormx(..)]` attributes, returning each of them together with its span.
+pub fn parse_attrs<A: Parse>(attrs: &[Attribute]) -> Result<Vec<(Span, A)>> {
     let attrs = attrs
         .iter()
         .filter(|a| a.path().is_ident("ormx"))
-        .map(|a| a.parse_args_with(Punctuated::<A, Token![,]>::parse_terminated))
+        .map(|a| a.parse_args_with(Punctuated::<WithSpan<A>, Token![,]>::parse_terminated))
         .collect::<Result<Vec<_>>>()?
         .into_iter()
         .flatten()
+        .map(|WithSpan(span, attr)| (span, attr))
         .collect();
     Ok(attrs)
 }
 
+struct WithSpan<A>(Span, A);
+
+impl<A: Parse> Parse for WithSpan<A> {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(WithSpan(input.span(), input.parse()?))
+    }
+}
+
 /// implements `syn::parse::Parse` for the given type
 macro_rules! impl_parse {
     // entry point
@@ -115,7 +270,7 @@ macro_rules! impl_parse {
                 let ident = input.parse::<syn::Ident>()?;
                 match &*ident.to_string() {
                     $( $s => (impl_parse!($($t)*))(input).map(Self::$v), )*
-                    _ => Err(input.error("unknown attribute"))
+                    _ => Err(crate::utils::unknown_attr(&ident, &[$( $s ),*])),
                 }
             }
         }
@@ -144,10 +299,24 @@ macro_rules! impl_parse {
 
 impl_parse!(TableAttr {
     "table" => Table(= String),
+    "schema" => Schema(= LitStr),
     "id" => Id(= Ident),
     "insertable" => Insertable((= Insertable)?),
     "deletable" => Deletable(),
-    "order_by" => OrderBy(= String)
+    "order_by" => OrderBy(= String),
+    "ddl" => Ddl(),
+    "unchecked" => Unchecked(),
+    "cache" => Cache(),
+    "lock" => Lock(),
+    "queue" => Queue(Queue),
+    "notify" => Notify((= String)?),
+    "backend" => Backend(= Ident)
+});
+
+impl_parse!(QueueAttr {
+    "status" => Status(= Ident),
+    "run_at" => RunAt(= Ident),
+    "attempts" => Attempts(= Ident)
 });
 
 impl_parse!(TableFieldAttr {
@@ -156,16 +325,29 @@ impl_parse!(TableFieldAttr {
     "get_optional" => GetOptional(Getter),
     "get_many" => GetMany(Getter),
     "set" => Set((= Ident)?),
+    "counter" => Counter(),
     "custom_type" => CustomType(),
     "default" => Default(),
     "by_ref" => ByRef(),
-    "insert_attribute" => InsertAttr(= AnyAttribute)
+    "sortable" => Sortable(),
+    "sql_type" => SqlType(= String),
+    "sql_default" => SqlDefault(= String),
+    "unique" => Unique(),
+    "index" => Index(),
+    "references" => References(= String),
+    "generate" => Generate(= LitStr),
+    "lookup" => Lookup(= Ident),
+    "insert_attribute" => InsertAttr(= AnyAttribute),
+    "skip" => Skip()
 });
 
 impl_parse!(PatchAttr {
     "table" => Table(= Path),
     "table_name" => TableName(= String),
-    "id" => Id(= String)
+    "schema" => Schema(= LitStr),
+    "id" => Id(= String),
+    "unchecked" => Unchecked(),
+    "backend" => Backend(= Ident)
 });
 
 impl_parse!(PatchFieldAttr {
@@ -174,6 +356,30 @@ impl_parse!(PatchFieldAttr {
     "by_ref" => ByRef()
 });
 
+impl_parse!(ProjectionAttr {
+    "table" => Table(= Path)
+});
+
+impl_parse!(ViewAttr {
+    "view" => View(= String),
+    "schema" => Schema(= LitStr),
+    "order_by" => OrderBy(= String),
+    "materialized" => Materialized(),
+    "unchecked" => Unchecked(),
+    "backend" => Backend(= Ident)
+});
+
+impl_parse!(JoinedAttr {
+    "from" => From(= Path),
+    "join" => Join(Join),
+    "order_by" => OrderBy(= String)
+});
+
+impl_parse!(JoinedFieldAttr {
+    "table" => Table(= Path),
+    "field" => Field(= Ident)
+});
+
 pub struct AnyAttribute(pub Vec<Attribute>);
 impl Parse for AnyAttribute {
     fn parse(input: ParseStream) -> Result<Self> {
@@ -0,0 +1,77 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::query_as;
+use crate::{backend::Backend, table::Table};
+
+/// Generates `for_each_batch`, walking the table ordered by its ID.
+/// Instead of an offset, every batch continues after the last ID of the previous one, so each
+/// query stays cheap regardless of how far into the table it is.
+pub fn batches<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
+    let vis = &table.vis;
+    let unchecked = table.unchecked;
+    let column_list = table.select_column_list();
+    let id = table.id.column();
+
+    let mut bindings = B::Bindings::default();
+    let first_sql = format!(
+        "SELECT {column_list} FROM {} ORDER BY {id} LIMIT {}",
+        table.name(),
+        bindings.next().unwrap()
+    );
+    let mut bindings = B::Bindings::default();
+    let next_sql = format!(
+        "SELECT {column_list} FROM {} WHERE {id} > {} ORDER BY {id} LIMIT {}",
+        table.name(),
+        bindings.next().unwrap(),
+        bindings.next().unwrap()
+    );
+
+    let id_ty = &table.id.ty;
+    let after = match table.id.custom_type && !unchecked {
+        true => quote!(after as #id_ty),
+        false => quote!(after),
+    };
+    let rows = table.rows();
+    let first = query_as(&rows, &first_sql, &[quote!(batch_size)]);
+    let next = query_as(&rows, &next_sql, &[after, quote!(batch_size)]);
+
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            /// Calls `f` with all rows of this table in batches of at most `batch_size` rows,
+            /// ordered by their ID.
+            /// Every batch is queried separately, continuing after the last ID of the previous
+            /// one, so no long-running query or transaction is kept open.
+            #vis async fn for_each_batch<F, Fut, E>(
+                db: &sqlx::Pool<#db>,
+                batch_size: i64,
+                mut f: F,
+            ) -> Result<(), E>
+            where
+                F: FnMut(Vec<Self>) -> Fut,
+                Fut: std::future::Future<Output = Result<(), E>>,
+                E: From<sqlx::Error>,
+            {
+                let mut last_id: Option<<Self as ormx::Table>::Id> = None;
+                loop {
+                    let batch = match last_id {
+                        None => #first.fetch_all(db).await?,
+                        Some(after) => #next.fetch_all(db).await?,
+                    };
+                    let Some(last) = batch.last() else {
+                        return Ok(());
+                    };
+                    last_id = Some(<Self as ormx::Table>::id(last));
+                    let exhausted = (batch.len() as i64) < batch_size;
+                    f(batch).await?;
+                    if exhausted {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Type, Visibility};
+
+use super::query_as;
+use crate::{
+    backend::Backend,
+    table::{Table, TableField},
+};
+
+/// Generates an `impl Cached for <Table>` block for tables using `#[ormx(cache)]`.
+pub fn impl_cache<B: Backend>(table: &Table<B>) -> TokenStream {
+    if !table.cache {
+        return quote!();
+    }
+
+    let table_ident = &table.ident;
+    quote! {
+        impl ormx::Cached for #table_ident {
+            fn slot() -> &'static ormx::exports::CacheSlot<Self> {
+                static SLOT: ormx::exports::CacheSlot<#table_ident> =
+                    ormx::exports::CacheSlot::new();
+                &SLOT
+            }
+        }
+    }
+}
+
+/// Generates a `get_one` getter which looks up the row in the cache of the table.
+/// The value of the field is mapped to the ID of the row by an index, and the cached row is only
+/// returned if the field still has that value.
+pub fn cached_get_one<B: Backend>(
+    table: &Table<B>,
+    field: &TableField<B>,
+    vis: &Visibility,
+    ident: &Ident,
+    by_ty: &Type,
+    sql: &str,
+) -> TokenStream {
+    let db = B::database();
+    let table_ident = &table.ident;
+    let field_ident = &field.field;
+    let field_ty = &field.ty;
+    let query = query_as(&table.rows(), sql, &[quote!(by)]);
+
+    quote! {
+        #vis async fn #ident(
+            db: impl sqlx::Executor<'_, Database = #db>,
+            by: #by_ty,
+        ) -> sqlx::Result<Self> {
+            static INDEX: ormx::exports::CacheIndex<
+                #field_ty,
+                <#table_ident as ormx::Table>::Id,
+            > = ormx::exports::CacheIndex::new();
+
+            let cache = <Self as ormx::Cached>::cache();
+            let key: #field_ty = by.to_owned();
+            if let Some(id) = ormx::Cache::get(INDEX.get(), &key) {
+                if let Some(row) = cache.get(&id) {
+                    if row.#field_ident == key {
+                        return Ok(row);
+                    }
+                }
+            }
+
+            let fill = <Self as ormx::Cached>::begin_fill(&db);
+            let row = #query
+                .fetch_one(db)
+                .await?;
+            if <Self as ormx::Cached>::fill(fill, &row) {
+                let id = <Self as ormx::Table>::id(&row);
+                ormx::Cache::insert(INDEX.get(), key, id);
+            }
+            Ok(row)
+        }
+    }
+}
+
+/// Removes `self` from the cache after it has been written to the database.
+/// The row is not cached in its new state, since the write may still be rolled back.
+pub fn invalidate_cached<B: Backend>(table: &Table<B>) -> TokenStream {
+    if !table.cache {
+        return quote!();
+    }
+
+    quote! {
+        <Self as ormx::Table>::invalidate_cached(<Self as ormx::Table>::id(self));
+    }
+}
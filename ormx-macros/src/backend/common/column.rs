@@ -0,0 +1,58 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{ext::IdentExt, Ident};
+
+use crate::{
+    backend::Backend,
+    table::{Table, TableField},
+    utils::to_upper_camel_case,
+};
+
+/// Generates the `{Table}Column` enum, containing a variant for every column of the table.
+pub fn column_enum<B: Backend>(table: &Table<B>) -> TokenStream {
+    let vis = &table.vis;
+    let column_ident = table.column_ident();
+
+    let variants: Vec<Ident> = table.fields.iter().map(column_variant).collect();
+    let sql = table.fields.iter().map(|field| field.column());
+    let names = table.fields.iter().map(|field| field.column_name());
+
+    let serde_derive = if cfg!(feature = "serde") {
+        quote!(#[derive(::serde::Serialize, ::serde::Deserialize)])
+    } else {
+        quote!()
+    };
+
+    quote! {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #serde_derive
+        #vis enum #column_ident {
+            #( #variants, )*
+        }
+
+        impl #column_ident {
+            /// All columns of the table, in the order of the fields of the struct.
+            #vis const ALL: &'static [Self] = &[ #( Self::#variants, )* ];
+
+            /// Returns the quoted column, ready to be used in a query.
+            #vis const fn as_sql(self) -> &'static str {
+                match self {
+                    #( Self::#variants => #sql, )*
+                }
+            }
+
+            /// Returns the unquoted name of the column.
+            #vis const fn name(self) -> &'static str {
+                match self {
+                    #( Self::#variants => #names, )*
+                }
+            }
+        }
+    }
+}
+
+/// Returns the variant of the `{Table}Column` enum for the given field.
+pub fn column_variant<B: Backend>(field: &TableField<B>) -> Ident {
+    let name = to_upper_camel_case(&field.field.unraw().to_string());
+    Ident::new(&name, Span::call_site())
+}
@@ -0,0 +1,110 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{ext::IdentExt, Ident};
+
+use super::{invalidate_cached, query, query_scalar};
+use crate::{
+    backend::Backend,
+    table::{Table, TableField},
+};
+
+/// Generates `increment_{field}` and `decrement_{field}` for fields using `#[ormx(counter)]`.
+/// The new value is computed by the database, so concurrent updates are not lost.
+pub fn counters<B: Backend>(table: &Table<B>) -> TokenStream {
+    let mut counters = TokenStream::new();
+    for field in table.fields.iter().filter(|field| field.counter.is_some()) {
+        let name = field.field.unraw();
+        for (prefix, op) in [("increment", '+'), ("decrement", '-')] {
+            let ident = Ident::new(&format!("{prefix}_{name}"), Span::call_site());
+            counters.extend(counter(table, field, &ident, op));
+        }
+    }
+
+    if counters.is_empty() {
+        return quote!();
+    }
+
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #counters
+        }
+    }
+}
+
+fn counter<B: Backend>(
+    table: &Table<B>,
+    field: &TableField<B>,
+    ident: &Ident,
+    op: char,
+) -> TokenStream {
+    let db = B::database();
+    let vis = &table.vis;
+    let unchecked = table.unchecked;
+    let field_ident = &field.field;
+    let field_ty = &field.ty;
+    let column = field.column();
+
+    let mut bindings = B::Bindings::default();
+    let update_sql = format!(
+        "UPDATE {} SET {column} = {column} {op} {} WHERE {} = {}",
+        table.name(),
+        bindings.next().unwrap(),
+        table.id.column(),
+        bindings.next().unwrap(),
+    );
+
+    let delta = match (unchecked, field.custom_type) {
+        (true, _) => quote!(&delta),
+        (false, true) => quote!(delta as #field_ty),
+        (false, false) => quote!(delta),
+    };
+    let id = quote!(<Self as ormx::Table>::id(self));
+    let invalidate_cached = invalidate_cached(table);
+    let doc = format!(
+        "{}s `{}` by `delta` in the database, and sets it to the resulting value.",
+        if op == '+' { "Increment" } else { "Decrement" },
+        field_ident.unraw()
+    );
+
+    let update = if B::UPDATE_RETURNING {
+        let sql = format!("{update_sql} RETURNING {}", field.fmt_for_select(unchecked));
+        let query = query_scalar(unchecked, field_ty, &sql, &[delta, id]);
+        quote! {
+            let mut conn = sqlx::Acquire::acquire(db).await?;
+            self.#field_ident = #query.fetch_one(&mut *conn).await?;
+        }
+    } else {
+        // the row stays locked until the end of the transaction, so the selected value is the
+        // result of this update
+        let update = query(unchecked, &update_sql, &[delta, id.clone()]);
+        let select_sql = format!(
+            "SELECT {} FROM {} WHERE {} = {}",
+            field.fmt_for_select(unchecked),
+            table.name(),
+            table.id.column(),
+            B::Bindings::default().next().unwrap()
+        );
+        let select = query_scalar(unchecked, field_ty, &select_sql, &[id]);
+        quote! {
+            let mut tx = sqlx::Acquire::begin(db).await?;
+            #update.execute(&mut *tx).await?;
+            self.#field_ident = #select.fetch_one(&mut *tx).await?;
+            tx.commit().await?;
+        }
+    };
+
+    quote! {
+        #[doc = #doc]
+        #vis async fn #ident<'c>(
+            &mut self,
+            db: impl sqlx::Acquire<'c, Database = #db> + Send,
+            delta: #field_ty,
+        ) -> sqlx::Result<()> {
+            #update
+            #invalidate_cached
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Error;
+
+use crate::{
+    backend::Backend,
+    table::{Table, TableField},
+    utils::collect_all,
+};
+
+use super::types::{infer_sql_type, option_inner};
+
+/// Generates `create_table_sql`, which returns the DDL for the table in the given dialect.
+pub fn ddl<B: Backend>(table: &Table<B>) -> TokenStream {
+    if !table.ddl {
+        return quote!();
+    }
+
+    let columns = match collect_all(table.fields.iter().map(|field| column(table, field))) {
+        Ok(columns) => columns,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let vis = &table.vis;
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    let schema = match table.schema_name() {
+        Some(schema) => quote!(Some(#schema)),
+        None => quote!(None),
+    };
+    let table_name = table.table_name();
+
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #vis fn create_table_sql(dialect: ormx::Dialect) -> String {
+                ormx::ddl::create_table(dialect, #schema, #table_name, &[
+                    #( #columns, )*
+                ])
+            }
+        }
+    }
+}
+
+fn column<B: Backend>(table: &Table<B>, field: &TableField<B>) -> syn::Result<TokenStream> {
+    let (ty, nullable) = match option_inner(&field.ty) {
+        Some(inner) => (inner, true),
+        None => (&field.ty, false),
+    };
+    let sql_type = match &field.sql_type {
+        Some(sql_type) => quote!(ormx::ddl::SqlType::Custom(#sql_type)),
+        None => infer_sql_type(ty).ok_or_else(|| {
+            Error::new_spanned(
+                &field.ty,
+                "can not infer the SQL type of this field, use #[ormx(sql_type = \"..\")]",
+            )
+        })?,
+    };
+
+    let name = field.column_name();
+    let primary_key = field.field == table.id.field;
+    let generated = field.default;
+    // a nullable column defaults to NULL
+    if generated && !primary_key && !nullable && field.sql_default.is_none() {
+        return Err(Error::new(
+            field.field.span(),
+            "the value of this #[ormx(default)] column is generated by the database, \
+             so #[ormx(ddl)] requires #[ormx(sql_default = \"..\")]",
+        ));
+    }
+    let default = optional_str(field.sql_default.as_deref());
+    let unique = field.unique;
+    let index = field.index;
+    let references = optional_str(field.references.as_deref());
+
+    Ok(quote! {
+        ormx::ddl::Column {
+            name: #name,
+            sql_type: #sql_type,
+            nullable: #nullable,
+            primary_key: #primary_key,
+            generated: #generated,
+            default: #default,
+            unique: #unique,
+            index: #index,
+            references: #references,
+        }
+    })
+}
+
+fn optional_str(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
@@ -0,0 +1,105 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::{field_getters, from_row, query_as, Rows};
+use crate::{backend::Backend, joined::Joined};
+
+/// Generates an `impl <Joined>` block with accessors selecting from all joined tables.
+pub fn impl_joined<B: Backend>(joined: &Joined<B>) -> TokenStream {
+    let db = B::database();
+    let from = joined.from();
+    let from_path = &joined.from_path;
+    let ident = &joined.ident;
+    let vis = &joined.vis;
+    let return_type = crate::utils::stream!(sqlx::Result<Self>);
+    let column_list = joined.select_column_list();
+    let tables = joined.joined_tables();
+    let order_by = match &joined.order_by {
+        Some(by) => by.clone(),
+        None => format!("{}.{} DESC", from.name(), from.id.column()),
+    };
+
+    let get_sql = format!(
+        "SELECT {} FROM {} WHERE {}.{} = {}",
+        column_list,
+        tables,
+        from.name(),
+        from.id.column(),
+        B::Bindings::default().next().unwrap()
+    );
+    let all_sql = format!("SELECT {column_list} FROM {tables} ORDER BY {order_by}");
+    let mut bindings = B::Bindings::default();
+    let all_paginated_sql = format!(
+        "SELECT {column_list} FROM {tables} ORDER BY {order_by} LIMIT {} OFFSET {}",
+        bindings.next().unwrap(),
+        bindings.next().unwrap()
+    );
+    let unchecked = from.unchecked;
+    let rows = Rows::new(unchecked);
+    let getters = field_getters(vis, &tables, &column_list, &joined.fields, &rows, |field| {
+        joined.qualified_column(field)
+    });
+
+    let get = query_as(&rows, &get_sql, &[quote!(id)]);
+    let all = query_as(&rows, &all_sql, &[]);
+    let all_paginated = query_as(&rows, &all_paginated_sql, &[quote!(limit), quote!(offset)]);
+    let from_row = from_row(unchecked, ident, &Default::default(), &joined.fields, &[]);
+
+    quote! {
+        impl #ident {
+            /// Queries the row of the given id of the first table.
+            #vis async fn get(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                id: <#from_path as ormx::Table>::Id,
+            ) -> sqlx::Result<Self> {
+                #get
+                    .fetch_one(db)
+                    .await
+            }
+
+            /// Streams all rows.
+            #vis fn stream_all<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+            ) -> #return_type + 'a {
+                #all
+                    .fetch(db)
+            }
+
+            /// Streams at most `limit` rows, skipping the first `offset` rows.
+            #vis fn stream_all_paginated<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                offset: i64,
+                limit: i64,
+            ) -> #return_type + 'a {
+                #all_paginated
+                    .fetch(db)
+            }
+
+            /// Loads all rows.
+            #vis async fn all(
+                db: impl sqlx::Executor<'_, Database = #db>,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all(db).try_collect().await
+            }
+
+            /// Loads at most `limit` rows, skipping the first `offset` rows.
+            #vis async fn all_paginated(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                offset: i64,
+                limit: i64,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all_paginated(db, offset, limit)
+                    .try_collect()
+                    .await
+            }
+
+            #getters
+        }
+
+        #from_row
+    }
+}
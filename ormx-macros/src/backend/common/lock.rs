@@ -0,0 +1,122 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use super::{get_many, get_one, get_optional, get_sql, getter_sql};
+use crate::{backend::Backend, table::Table};
+
+/// The kind of lock taken on the selected rows.
+#[derive(Clone, Copy)]
+enum LockMode {
+    Update,
+    Share,
+}
+
+/// What happens if a selected row is already locked.
+#[derive(Clone, Copy)]
+enum LockWait {
+    Wait,
+    NoWait,
+    SkipLocked,
+}
+
+const LOCKS: [(LockMode, LockWait); 6] = [
+    (LockMode::Update, LockWait::Wait),
+    (LockMode::Update, LockWait::NoWait),
+    (LockMode::Update, LockWait::SkipLocked),
+    (LockMode::Share, LockWait::Wait),
+    (LockMode::Share, LockWait::NoWait),
+    (LockMode::Share, LockWait::SkipLocked),
+];
+
+/// Returns the locking clause appended to a `SELECT`, e.g `FOR UPDATE SKIP LOCKED`.
+fn lock_clause<B: Backend>(mode: LockMode, wait: LockWait) -> String {
+    let mode = match mode {
+        LockMode::Update => "FOR UPDATE",
+        LockMode::Share => B::FOR_SHARE,
+    };
+    match wait {
+        LockWait::Wait => mode.to_owned(),
+        LockWait::NoWait => format!("{mode} NOWAIT"),
+        LockWait::SkipLocked => format!("{mode} SKIP LOCKED"),
+    }
+}
+
+/// Returns the suffix of the locking variant of a getter, e.g `for_update_skip_locked`.
+fn suffix(mode: LockMode, wait: LockWait) -> &'static str {
+    match (mode, wait) {
+        (LockMode::Update, LockWait::Wait) => "for_update",
+        (LockMode::Update, LockWait::NoWait) => "for_update_nowait",
+        (LockMode::Update, LockWait::SkipLocked) => "for_update_skip_locked",
+        (LockMode::Share, LockWait::Wait) => "for_share",
+        (LockMode::Share, LockWait::NoWait) => "for_share_nowait",
+        (LockMode::Share, LockWait::SkipLocked) => "for_share_skip_locked",
+    }
+}
+
+/// Generates an `impl <Table>` block containing the locking variants of `get` and the getters for
+/// tables using `#[ormx(lock)]`, e.g `get_for_update` or `by_email_for_share_nowait`.
+/// Variants of `get_one` getters skipping locked rows return an `Option`, and variants of
+/// `get_many` getters lock the rows ordered by their ID.
+pub fn locks<B: Backend>(table: &Table<B>) -> TokenStream {
+    if !table.lock {
+        return quote!();
+    }
+
+    let vis = &table.vis;
+    let rows = table.rows();
+    let column_list = table.select_column_list();
+    let mut getters = TokenStream::new();
+
+    for (mode, wait) in LOCKS {
+        let clause = lock_clause::<B>(mode, wait);
+        let variant = |func: &Ident| {
+            Ident::new(
+                &format!("{}_{}", func, suffix(mode, wait)),
+                Span::call_site(),
+            )
+        };
+        // unlike a locked row, a skipped row may exist
+        let get_one_or_skip = match wait {
+            LockWait::SkipLocked => get_optional::<B>,
+            _ => get_one::<B>,
+        };
+
+        let sql = format!("{} {}", get_sql(table), clause);
+        let get = Ident::new("get", Span::call_site());
+        getters.extend(get_one_or_skip(
+            vis,
+            &variant(&get),
+            &table.id.ty,
+            &sql,
+            &rows,
+        ));
+
+        for field in &table.fields {
+            let select = getter_sql::<B>(&table.name(), &column_list, &field.column());
+            let sql = format!("{select} {clause}");
+            // rows are locked in the same order by every query, so they can not deadlock
+            let many_sql = format!("{select} ORDER BY {} {clause}", table.id.column());
+            if let Some(getter) = &field.get_one {
+                let (func, arg) = getter.or_fallback(field);
+                getters.extend(get_one_or_skip(vis, &variant(&func), &arg, &sql, &rows));
+            }
+            if let Some(getter) = &field.get_optional {
+                let (func, arg) = getter.or_fallback(field);
+                getters.extend(get_optional::<B>(vis, &variant(&func), &arg, &sql, &rows));
+            }
+            if let Some(getter) = &field.get_many {
+                let (func, arg) = getter.or_fallback(field);
+                getters.extend(get_many::<B>(vis, &variant(&func), &arg, &many_sql, &rows));
+            }
+        }
+    }
+
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #getters
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+
+use super::{
+    table::{all_paginated_sql, all_sql, delete_sql, get_sql, update_sql},
+    types::{infer_sql_type, option_inner},
+};
+use crate::{
+    backend::Backend,
+    table::{Table, TableField},
+};
+
+/// Generates an `impl TableMeta for <Table>` block.
+/// Non-generic tables are additionally registered for `ormx::verify_all`.
+pub fn impl_table_meta<B: Backend>(table: &Table<B>) -> TokenStream {
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    let schema = match table.schema_name() {
+        Some(schema) => quote!(Some(#schema)),
+        None => quote!(None),
+    };
+    let table_name = table.table_name();
+    let id_column = table.id.column_name();
+    let columns = table.fields.iter().map(|field| column_meta(table, field));
+
+    let get_sql = get_sql(table);
+    let all_sql = all_sql(table);
+    let all_paginated_sql = all_paginated_sql(table);
+    let update_sql = update_sql(table);
+    let delete_sql = delete_sql(table);
+
+    let db = B::database();
+    let registration = if table.generics.params.is_empty() {
+        quote! {
+            ormx::exports::inventory::submit! {
+                ormx::exports::Registration::of::<#table_ident, #db>()
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        impl #impl_generics ormx::TableMeta for #table_ident #ty_generics #where_clause {
+            const SCHEMA: Option<&'static str> = #schema;
+            const TABLE_NAME: &'static str = #table_name;
+            const ID_COLUMN: &'static str = #id_column;
+            const COLUMNS: &'static [ormx::ColumnMeta] = &[ #( #columns, )* ];
+
+            const GET_SQL: &'static str = #get_sql;
+            const ALL_SQL: &'static str = #all_sql;
+            const ALL_PAGINATED_SQL: &'static str = #all_paginated_sql;
+            const UPDATE_SQL: &'static str = #update_sql;
+            const DELETE_SQL: &'static str = #delete_sql;
+        }
+
+        #registration
+    }
+}
+
+fn column_meta<B: Backend>(table: &Table<B>, field: &TableField<B>) -> TokenStream {
+    let (ty, nullable) = match option_inner(&field.ty) {
+        Some(inner) => (inner, true),
+        None => (&field.ty, false),
+    };
+    let sql_type = match (&field.sql_type, field.custom_type) {
+        (Some(sql_type), _) => quote!(Some(ormx::ddl::SqlType::Custom(#sql_type))),
+        (None, true) => quote!(None),
+        (None, false) => match infer_sql_type(ty) {
+            Some(sql_type) => quote!(Some(#sql_type)),
+            None => quote!(None),
+        },
+    };
+    let name = field.field.unraw().to_string();
+    let column = field.column_name();
+    let custom_type = field.custom_type;
+    let default = field.default;
+    let read_only = field.field == table.id.field;
+
+    quote! {
+        ormx::ColumnMeta {
+            field: #name,
+            column: #column,
+            nullable: #nullable,
+            custom_type: #custom_type,
+            default: #default,
+            read_only: #read_only,
+            sql_type: #sql_type,
+        }
+    }
+}
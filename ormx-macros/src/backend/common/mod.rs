@@ -1,97 +1,244 @@
 //! Common functionality used for all database backends
 
-use proc_macro2::TokenStream;
+pub use batch::*;
+pub use cache::*;
+pub use column::*;
+pub use counter::*;
+pub use ddl::*;
+pub use joined::*;
+pub use lock::*;
+pub use meta::*;
+use proc_macro2::{Span, TokenStream};
+pub use projection::*;
+pub use query::*;
 use quote::quote;
-use syn::{Ident, Type, Visibility};
+pub use sort::*;
+use syn::{
+    ext::IdentExt,
+    visit_mut::{self, VisitMut},
+    Ident, Lifetime, Type, TypeReference, Visibility,
+};
 pub use table::*;
+#[cfg(feature = "mysql")]
+pub use types::is_integer;
+pub use view::*;
 
 use crate::{
     attrs::Insertable,
     backend::Backend,
-    patch::{Patch, PatchField},
-    table::Table,
+    patch::Patch,
+    table::{Table, TableField},
 };
 
+mod batch;
+mod cache;
+mod column;
+mod counter;
+mod ddl;
+mod joined;
+mod lock;
+mod meta;
+mod projection;
+mod query;
+mod sort;
 mod table;
+mod types;
+mod view;
 
 pub(crate) fn getters<B: Backend>(table: &Table<B>) -> TokenStream {
-    let column_list = table.select_column_list();
-    let vis = &table.vis;
+    let mut fields = table.fields.clone();
+    let mut cached_getters = TokenStream::new();
+    if table.cache {
+        for field in &mut fields {
+            if let Some(getter) = field.get_one.take() {
+                let (func, arg) = getter.or_fallback(field);
+                let sql =
+                    getter_sql::<B>(&table.name(), &table.select_column_list(), &field.column());
+                cached_getters.extend(cached_get_one(table, field, &table.vis, &func, &arg, &sql));
+            }
+        }
+    }
+
+    let getters = field_getters(
+        &table.vis,
+        &table.name(),
+        &table.select_column_list(),
+        &fields,
+        &table.rows(),
+        TableField::column,
+    );
+
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #getters
+            #cached_getters
+        }
+    }
+}
+
+/// Returns the query of a getter, selecting `column_list` from `name` by `column`.
+fn getter_sql<B: Backend>(name: &str, column_list: &str, column: &str) -> String {
+    format!(
+        "SELECT {} FROM {} WHERE {} = {}",
+        column_list,
+        name,
+        column,
+        B::Bindings::default().next().unwrap()
+    )
+}
+
+/// Generates the getters of the given fields, selecting `column_list` from `name`.
+/// `column` returns the column of a field used in the `WHERE` clause.
+pub(crate) fn field_getters<B: Backend>(
+    vis: &Visibility,
+    name: &str,
+    column_list: &str,
+    fields: &[TableField<B>],
+    rows: &Rows,
+    column: impl Fn(&TableField<B>) -> String,
+) -> TokenStream {
     let mut getters = TokenStream::new();
 
-    for field in table.fields.iter() {
-        let sql = format!(
-            "SELECT {} FROM {} WHERE {} = {}",
-            column_list,
-            table.name(),
-            field.column(),
-            B::Bindings::default().next().unwrap()
-        );
+    for field in fields {
+        let sql = getter_sql::<B>(name, column_list, &column(field));
 
         if let Some(getter) = &field.get_one {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_one(vis, &func, &arg, &sql));
+            getters.extend(get_one::<B>(vis, &func, &arg, &sql, rows));
         }
 
         if let Some(getter) = &field.get_optional {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_optional(vis, &func, &arg, &sql));
+            getters.extend(get_optional::<B>(vis, &func, &arg, &sql, rows));
         }
 
         if let Some(getter) = &field.get_many {
             let (func, arg) = getter.or_fallback(field);
-            getters.extend(get_many(vis, &func, &arg, &sql));
+            getters.extend(get_many::<B>(vis, &func, &arg, &sql, rows));
+            getters.extend(stream_many::<B>(vis, &func, &arg, &sql, rows));
         }
     }
 
-    let table_ident = &table.ident;
-    quote! {
-        impl #table_ident {
-            #getters
-        }
-    }
+    getters
 }
 
-pub fn get_one(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_one<B: Backend>(
+    vis: &Visibility,
+    ident: &Ident,
+    by_ty: &Type,
+    sql: &str,
+    rows: &Rows,
+) -> TokenStream {
+    let db = B::database();
+    let query = query_as(rows, sql, &[quote!(by)]);
     quote! {
         #vis async fn #ident(
-            db: impl sqlx::Executor<'_, Database = ormx::Db>,
+            db: impl sqlx::Executor<'_, Database = #db>,
             by: #by_ty,
         ) -> sqlx::Result<Self> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_one(db)
                 .await
         }
     }
 }
 
-pub fn get_optional(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_optional<B: Backend>(
+    vis: &Visibility,
+    ident: &Ident,
+    by_ty: &Type,
+    sql: &str,
+    rows: &Rows,
+) -> TokenStream {
+    let db = B::database();
+    let query = query_as(rows, sql, &[quote!(by)]);
     quote! {
         #vis async fn #ident(
-            db: impl sqlx::Executor<'_, Database = ormx::Db>,
+            db: impl sqlx::Executor<'_, Database = #db>,
             by: #by_ty,
         ) -> sqlx::Result<Option<Self>> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_optional(db)
                 .await
         }
     }
 }
 
-pub fn get_many(vis: &Visibility, ident: &Ident, by_ty: &Type, sql: &str) -> TokenStream {
+pub fn get_many<B: Backend>(
+    vis: &Visibility,
+    ident: &Ident,
+    by_ty: &Type,
+    sql: &str,
+    rows: &Rows,
+) -> TokenStream {
+    let db = B::database();
+    let query = query_as(rows, sql, &[quote!(by)]);
     quote! {
         #vis async fn #ident(
-            db: impl sqlx::Executor<'_, Database = ormx::Db>,
+            db: impl sqlx::Executor<'_, Database = #db>,
             by: #by_ty,
         ) -> sqlx::Result<Vec<Self>> {
-            sqlx::query_as!(Self, #sql, by)
+            #query
                 .fetch_all(db)
                 .await
         }
     }
 }
 
+/// Generates the streaming variant of a `get_many` getter, e.g `stream_by_team` for
+/// `get_by_team`.
+/// Elided lifetimes of `by_ty` are bound to the returned stream, which borrows the argument.
+pub fn stream_many<B: Backend>(
+    vis: &Visibility,
+    ident: &Ident,
+    by_ty: &Type,
+    sql: &str,
+    rows: &Rows,
+) -> TokenStream {
+    let db = B::database();
+    let name = ident.unraw().to_string();
+    let ident = Ident::new(
+        &format!("stream_{}", name.strip_prefix("get_").unwrap_or(&name)),
+        ident.span(),
+    );
+    let mut by_ty = by_ty.clone();
+    BindElidedLifetimes(Lifetime::new("'a", Span::call_site())).visit_type_mut(&mut by_ty);
+    let return_type = crate::utils::stream!(sqlx::Result<Self>);
+    let query = query_as(rows, sql, &[quote!(by)]);
+    quote! {
+        #vis fn #ident<'a, 'c: 'a>(
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
+            by: #by_ty,
+        ) -> #return_type + 'a {
+            #query
+                .fetch(db)
+        }
+    }
+}
+
+/// Replaces elided and anonymous lifetimes of references with the given lifetime.
+struct BindElidedLifetimes(Lifetime);
+
+impl VisitMut for BindElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        match &reference.lifetime {
+            Some(lifetime) if lifetime.ident != "_" => {}
+            _ => reference.lifetime = Some(self.0.clone()),
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.0.clone();
+        }
+    }
+}
+
 pub fn setters<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
     let vis = &table.vis;
     let mut setters = TokenStream::new();
 
@@ -111,22 +258,30 @@ pub fn setters<B: Backend>(table: &Table<B>) -> TokenStream {
             );
 
             let mut value = quote!(value);
-            if field.custom_type {
-                value = quote!(#value as #field_ty)
-            }
-            if field.by_ref {
-                value = quote!(&(#value));
+            if table.unchecked {
+                value = quote!(&#value);
+            } else {
+                if field.custom_type {
+                    value = quote!(#value as #field_ty)
+                }
+                if field.by_ref {
+                    value = quote!(&(#value));
+                }
             }
+            let id = quote!(<Self as ormx::Table>::id(self));
+            let query = query(table.unchecked, &sql, &[value, id]);
+            let invalidate_cached = invalidate_cached(table);
             setters.extend(quote! {
                 #vis async fn #fn_name(
                     &mut self,
-                    db: impl sqlx::Executor<'_, Database = ormx::Db>,
+                    db: impl sqlx::Executor<'_, Database = #db>,
                     value: #field_ty
                 ) -> sqlx::Result<()> {
-                    sqlx::query!(#sql, #value, <Self as ormx::Table>::id(self))
+                    #query
                         .execute(db)
                         .await?;
                     self.#field_ident = value;
+                    #invalidate_cached
                     Ok(())
                 }
             })
@@ -134,45 +289,51 @@ pub fn setters<B: Backend>(table: &Table<B>) -> TokenStream {
     }
 
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
     quote! {
-        impl #table_ident {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
             #setters
         }
     }
 }
 
 pub(crate) fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
+    let db = B::database();
     let patch_ident = &patch.ident;
+    let (impl_generics, ty_generics, where_clause) = patch.generics.split_for_impl();
     let table_path = &patch.table;
     let field_idents = &patch
         .fields
         .iter()
         .map(|field| &field.ident)
         .collect::<Vec<&Ident>>();
-    let query_args = &patch
+    let mut query_args = patch
         .fields
         .iter()
-        .map(PatchField::fmt_as_argument)
+        .map(|field| field.fmt_as_argument(patch.unchecked))
         .collect::<Vec<TokenStream>>();
+    query_args.push(quote!(id));
 
     let mut bindings = B::Bindings::default();
     let mut assignments = Vec::with_capacity(patch.fields.len());
     for field in &patch.fields {
-        let fragment = format!("{} = {}", field.column, bindings.next().unwrap());
+        let fragment = format!("{} = {}", field.column::<B>(), bindings.next().unwrap());
         assignments.push(fragment);
     }
     let assignments = assignments.join(", ");
 
     let sql = format!(
         "UPDATE {} SET {} WHERE {} = {}",
-        &patch.table_name,
+        patch.name::<B>(),
         assignments,
-        patch.id,
+        patch.id_column::<B>(),
         bindings.next().unwrap()
     );
 
+    let query = query(patch.unchecked, &sql, &query_args);
+
     quote! {
-        impl ormx::Patch for #patch_ident {
+        impl #impl_generics ormx::Patch for #patch_ident #ty_generics #where_clause {
             type Table = #table_path;
 
             fn apply_to(self, entity: &mut Self::Table) {
@@ -181,12 +342,13 @@ pub(crate) fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
 
             async fn patch_row<'a, 'c: 'a>(
                 &'a self,
-                db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
                 id: <Self::Table as ormx::Table>::Id,
             ) -> sqlx::Result<()> {
-                sqlx::query!(#sql, #( self.#query_args, )* id)
+                #query
                     .execute(db)
                     .await?;
+                <Self::Table as ormx::Table>::invalidate_cached(id);
                 Ok(())
             }
         }
@@ -199,12 +361,18 @@ pub(crate) fn insert_struct<B: Backend>(table: &Table<B>) -> TokenStream {
         None => return quote!(),
     };
     let vis = &table.vis;
-    let insert_fields = table.insertable_fields().map(|field| {
-        let ident = &field.field;
-        let ty = &field.ty;
-        let attrs = &field.insert_attrs;
-        quote!(#(#attrs)* #vis #ident: #ty)
-    });
+    let generics = &table.generics;
+    let where_clause = &generics.where_clause;
+    // skipped fields are kept, so that every type parameter is still used
+    let insert_fields = table
+        .insertable_fields()
+        .chain(&table.skipped)
+        .map(|field| {
+            let ident = &field.field;
+            let ty = &field.ty;
+            let attrs = &field.insert_attrs;
+            quote!(#(#attrs)* #vis #ident: #ty)
+        });
 
     let serde_derive = if cfg!(feature = "serde") {
         quote! {
@@ -219,7 +387,7 @@ pub(crate) fn insert_struct<B: Backend>(table: &Table<B>) -> TokenStream {
         #(#attrs)*
         #[derive(Debug, Clone)]
         #serde_derive
-        #vis struct #ident {
+        #vis struct #ident #generics #where_clause {
             #( #insert_fields, )*
         }
 
@@ -229,9 +397,11 @@ pub(crate) fn insert_struct<B: Backend>(table: &Table<B>) -> TokenStream {
 
 fn impl_from_for_insert_struct<B: Backend>(table: &Table<B>, insert_struct: &Ident) -> TokenStream {
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
 
     let fields = table
         .insertable_fields()
+        .chain(&table.skipped)
         .map(|field| {
             let ident = &field.field;
             quote!(#ident: v.#ident,)
@@ -239,8 +409,10 @@ fn impl_from_for_insert_struct<B: Backend>(table: &Table<B>, insert_struct: &Ide
         .collect::<TokenStream>();
 
     quote! {
-        impl From<#table_ident> for #insert_struct {
-            fn from(v: #table_ident) -> Self {
+        impl #impl_generics From<#table_ident #ty_generics> for #insert_struct #ty_generics
+        #where_clause
+        {
+            fn from(v: #table_ident #ty_generics) -> Self {
                 Self {
                     #fields
                 }
@@ -0,0 +1,120 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::{field_getters, from_row, query_as, table::order_by, Rows};
+use crate::{backend::Backend, projection::Projection, table::TableField};
+
+/// Generates an `impl <Projection>` block with accessors which only select the columns of the
+/// projection.
+pub fn impl_projection<B: Backend>(projection: &Projection<B>) -> TokenStream {
+    let db = B::database();
+    let table = &projection.table;
+    let table_path = &projection.table_path;
+    let ident = &projection.ident;
+    let vis = &projection.vis;
+    let return_type = crate::utils::stream!(sqlx::Result<Self>);
+    let column_list = projection.select_column_list();
+
+    let get_sql = format!(
+        "SELECT {} FROM {} WHERE {} = {}",
+        column_list,
+        table.name(),
+        table.id.column(),
+        B::Bindings::default().next().unwrap()
+    );
+    let all_sql = format!(
+        "SELECT {} FROM {} ORDER BY {}",
+        column_list,
+        table.name(),
+        order_by(table)
+    );
+    let mut bindings = B::Bindings::default();
+    let all_paginated_sql = format!(
+        "SELECT {} FROM {} ORDER BY {} LIMIT {} OFFSET {}",
+        column_list,
+        table.name(),
+        order_by(table),
+        bindings.next().unwrap(),
+        bindings.next().unwrap()
+    );
+
+    let unchecked = table.unchecked;
+    let rows = Rows::new(unchecked);
+    let getters = field_getters(
+        vis,
+        &table.name(),
+        &column_list,
+        &table.fields,
+        &rows,
+        TableField::column,
+    );
+
+    let get = query_as(&rows, &get_sql, &[quote!(id)]);
+    let all = query_as(&rows, &all_sql, &[]);
+    let all_paginated = query_as(&rows, &all_paginated_sql, &[quote!(limit), quote!(offset)]);
+    let from_row = from_row(
+        unchecked,
+        ident,
+        &Default::default(),
+        &projection.fields,
+        &[],
+    );
+
+    quote! {
+        impl #ident {
+            /// Queries the row of the given id.
+            #vis async fn get(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                id: <#table_path as ormx::Table>::Id,
+            ) -> sqlx::Result<Self> {
+                #get
+                    .fetch_one(db)
+                    .await
+            }
+
+            /// Streams all rows, in the order of the table.
+            #vis fn stream_all<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+            ) -> #return_type + 'a {
+                #all
+                    .fetch(db)
+            }
+
+            /// Streams at most `limit` rows, skipping the first `offset` rows.
+            #vis fn stream_all_paginated<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                offset: i64,
+                limit: i64,
+            ) -> #return_type + 'a {
+                #all_paginated
+                    .fetch(db)
+            }
+
+            /// Loads all rows, in the order of the table.
+            #vis async fn all(
+                db: impl sqlx::Executor<'_, Database = #db>,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all(db).try_collect().await
+            }
+
+            /// Loads at most `limit` rows, skipping the first `offset` rows.
+            #vis async fn all_paginated(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                offset: i64,
+                limit: i64,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all_paginated(db, offset, limit)
+                    .try_collect()
+                    .await
+            }
+
+            #getters
+        }
+
+        #from_row
+    }
+}
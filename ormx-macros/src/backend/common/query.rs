@@ -0,0 +1,130 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{ext::IdentExt, Generics, Ident, Lifetime, LifetimeParam, Type};
+
+use crate::{backend::Backend, table::TableField};
+
+/// Describes how the rows returned by `query_as` are decoded into `Self`.
+pub struct Rows {
+    /// The query is not checked at compile time
+    pub unchecked: bool,
+    /// Fields decoded from the columns of a row
+    columns: Vec<Ident>,
+    /// Fields which are not columns, set to `Default::default()`
+    skipped: Vec<Ident>,
+}
+
+impl Rows {
+    /// Rows decoded into a struct consisting only of columns.
+    pub fn new(unchecked: bool) -> Self {
+        Self::skipping(unchecked, vec![], vec![])
+    }
+
+    /// Rows decoded into a struct with fields which are not columns.
+    pub fn skipping(unchecked: bool, columns: Vec<Ident>, skipped: Vec<Ident>) -> Self {
+        Self {
+            unchecked,
+            columns,
+            skipped,
+        }
+    }
+}
+
+/// Generates a query returning `Self`.
+/// If `unchecked` is set, the query is not checked at compile time, and the rows are decoded
+/// using the `FromRow` implementation generated by `from_row`.
+/// Since `query_as!` has to set every field from a column, checked queries of a struct with
+/// skipped fields construct it from the record returned by `query!` instead.
+pub fn query_as(rows: &Rows, sql: &str, args: &[TokenStream]) -> TokenStream {
+    let Rows {
+        unchecked,
+        columns,
+        skipped,
+    } = rows;
+    if *unchecked {
+        quote!(sqlx::query_as::<_, Self>(#sql) #( .bind(#args) )*)
+    } else if skipped.is_empty() {
+        quote!(sqlx::query_as!(Self, #sql #( , #args )*))
+    } else {
+        quote! {
+            sqlx::query!(#sql #( , #args )*).map(|row| Self {
+                #( #columns: row.#columns, )*
+                #( #skipped: ::std::default::Default::default(), )*
+            })
+        }
+    }
+}
+
+/// Generates a query which returns a single column of type `ty`.
+pub fn query_scalar(unchecked: bool, ty: &Type, sql: &str, args: &[TokenStream]) -> TokenStream {
+    if unchecked {
+        quote!(sqlx::query_scalar::<_, #ty>(#sql) #( .bind(#args) )*)
+    } else {
+        quote!(sqlx::query_scalar!(#sql #( , #args )*))
+    }
+}
+
+/// Generates a query. When checked, the columns of a returned row are accessible as fields,
+/// otherwise they have to be accessed using `Row::try_get`, see `get_column`.
+pub fn query(unchecked: bool, sql: &str, args: &[TokenStream]) -> TokenStream {
+    if unchecked {
+        quote!(sqlx::query(#sql) #( .bind(#args) )*)
+    } else {
+        quote!(sqlx::query!(#sql #( , #args )*))
+    }
+}
+
+/// Accesses the column of `field` in `row`, which was returned by `query`.
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub fn get_column<B: Backend>(unchecked: bool, row: &Ident, field: &TableField<B>) -> TokenStream {
+    let ident = &field.field;
+    if unchecked {
+        let name = ident.unraw().to_string();
+        quote!(sqlx::Row::try_get(&#row, #name)?)
+    } else {
+        quote!(#row.#ident)
+    }
+}
+
+/// Generates an `impl FromRow for <ident>` block for unchecked queries.
+/// The columns are expected to be named like the fields, see `TableField::fmt_for_select`.
+/// Fields in `skipped` are set to `Default::default()`.
+pub fn from_row<B: Backend>(
+    unchecked: bool,
+    ident: &Ident,
+    generics: &Generics,
+    fields: &[TableField<B>],
+    skipped: &[TableField<B>],
+) -> TokenStream {
+    if !unchecked {
+        return quote!();
+    }
+
+    let db = B::database();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mut generics = generics.clone();
+    let lifetime = Lifetime::new("'__r", Span::call_site());
+    generics
+        .params
+        .insert(0, LifetimeParam::new(lifetime.clone()).into());
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    let field_idents = fields.iter().map(|field| &field.field);
+    let names = fields.iter().map(|field| field.field.unraw().to_string());
+    let skipped = skipped.iter().map(|field| &field.field);
+
+    quote! {
+        impl #impl_generics sqlx::FromRow<#lifetime, <#db as sqlx::Database>::Row>
+            for #ident #ty_generics #where_clause
+        {
+            fn from_row(row: &#lifetime <#db as sqlx::Database>::Row) -> sqlx::Result<Self> {
+                use sqlx::Row;
+
+                Ok(Self {
+                    #( #field_idents: row.try_get(#names)?, )*
+                    #( #skipped: ::std::default::Default::default(), )*
+                })
+            }
+        }
+    }
+}
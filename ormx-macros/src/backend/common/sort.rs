@@ -0,0 +1,109 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{ext::IdentExt, Ident};
+
+use super::{column_variant, query_as};
+use crate::{backend::Backend, table::Table, utils::to_upper_camel_case};
+
+/// Generates the `{Table}Sort` enum and the `stream_all_sorted` and `all_sorted` accessors.
+/// Every variant of the enum maps to its own query, so that the order can be selected at runtime
+/// while every query is still checked at compile time.
+pub fn sort<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
+    let mut sortable = table
+        .fields
+        .iter()
+        .filter(|field| field.sortable.is_some())
+        .peekable();
+    if sortable.peek().is_none() {
+        return quote!();
+    }
+
+    let vis = &table.vis;
+    let table_ident = &table.ident;
+    let sort_ident = table.sort_ident();
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    let return_type = crate::utils::stream!(sqlx::Result<Self>);
+    let column_list = table.select_column_list();
+
+    let column_ident = table.column_ident();
+    let mut variants = vec![];
+    let mut arms = vec![];
+    let mut column_arms = vec![];
+    for field in sortable {
+        let name = to_upper_camel_case(&field.field.unraw().to_string());
+        let column = column_variant(field);
+        for (suffix, direction) in [("Asc", "ASC"), ("Desc", "DESC")] {
+            let variant = Ident::new(&format!("{name}{suffix}"), Span::call_site());
+            let mut order_by = format!("{} {direction}", field.column());
+            if field.field != table.id.field {
+                order_by += &format!(", {} {direction}", table.id.column());
+            }
+            let mut bindings = B::Bindings::default();
+            let sql = format!(
+                "SELECT {} FROM {} ORDER BY {} LIMIT {} OFFSET {}",
+                column_list,
+                table.name(),
+                order_by,
+                bindings.next().unwrap(),
+                bindings.next().unwrap()
+            );
+
+            let query = query_as(&table.rows(), &sql, &[quote!(limit), quote!(offset)]);
+            arms.push(quote! {
+                #sort_ident::#variant => #query.fetch(db),
+            });
+            column_arms.push(quote!(#sort_ident::#variant => #column_ident::#column,));
+            variants.push(variant);
+        }
+    }
+
+    let serde_derive = if cfg!(feature = "serde") {
+        quote!(#[derive(::serde::Serialize, ::serde::Deserialize)])
+    } else {
+        quote!()
+    };
+
+    quote! {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #serde_derive
+        #vis enum #sort_ident {
+            #( #variants, )*
+        }
+
+        impl #sort_ident {
+            /// Returns the column by which the rows are sorted.
+            #vis const fn column(self) -> #column_ident {
+                match self {
+                    #( #column_arms )*
+                }
+            }
+        }
+
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #vis fn stream_all_sorted<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                sort: #sort_ident,
+                offset: i64,
+                limit: i64,
+            ) -> #return_type + 'a {
+                match sort {
+                    #( #arms )*
+                }
+            }
+
+            #vis async fn all_sorted(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                sort: #sort_ident,
+                offset: i64,
+                limit: i64,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all_sorted(db, sort, offset, limit)
+                    .try_collect()
+                    .await
+            }
+        }
+    }
+}
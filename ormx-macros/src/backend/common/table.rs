@@ -1,24 +1,31 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
-use crate::{
-    backend::Backend,
-    table::{Table, TableField},
-};
+use super::{from_row, invalidate_cached, query, query_as};
+use crate::{backend::Backend, table::Table};
 
 pub fn impl_table<B: Backend>(table: &Table<B>) -> TokenStream {
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
     let id_ident = &table.id.field;
     let id_ty = &table.id.ty;
-    let column_list = table.select_column_list();
+    let db = B::database();
 
-    let get = get::<B>(table, &column_list);
-    let stream_all = stream_all(table, &column_list);
-    let stream_all_paginated = stream_all_paginated::<B>(table, &column_list);
+    let get = get::<B>(table);
+    let stream_all = stream_all(table);
+    let stream_all_paginated = stream_all_paginated::<B>(table);
     let update = update::<B>(table);
+    let from_row = from_row(
+        table.unchecked,
+        table_ident,
+        &table.generics,
+        &table.fields,
+        &table.skipped,
+    );
 
     quote! {
-        impl ormx::Table for #table_ident {
+        impl #impl_generics ormx::Table for #table_ident #ty_generics #where_clause {
+            type Database = #db;
             type Id = #id_ty;
 
             fn id(&self) -> Self::Id { self.#id_ident }
@@ -28,6 +35,8 @@ pub fn impl_table<B: Backend>(table: &Table<B>) -> TokenStream {
             #stream_all_paginated
             #update
         }
+
+        #from_row
     }
 }
 
@@ -37,37 +46,74 @@ pub fn impl_delete<B: Backend>(table: &Table<B>) -> TokenStream {
     }
 
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
     let delete = delete::<B>(table);
 
     quote! {
-        impl ormx::Delete for #table_ident {
+        impl #impl_generics ormx::Delete for #table_ident #ty_generics #where_clause {
             #delete
         }
     }
 }
 
-fn get<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
-    let get_sql = format!(
+pub fn get_sql<B: Backend>(table: &Table<B>) -> String {
+    format!(
         "SELECT {} FROM {} WHERE {} = {}",
-        column_list,
+        table.select_column_list(),
         table.name(),
         table.id.column(),
         B::Bindings::default().next().unwrap()
-    );
+    )
+}
+
+fn get<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
+    let get_sql = get_sql(table);
+    let query = query_as(&table.rows(), &get_sql, &[quote!(id)]);
+
+    if table.cache {
+        return quote! {
+            async fn get<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                id: Self::Id,
+            ) -> sqlx::Result<Self> {
+                if let Some(row) = <Self as ormx::Cached>::cache().get(&id) {
+                    return Ok(row);
+                }
+                let fill = <Self as ormx::Cached>::begin_fill(&db);
+                let row = <Self as ormx::Table>::get_uncached(db, id).await?;
+                <Self as ormx::Cached>::fill(fill, &row);
+                Ok(row)
+            }
+
+            async fn get_uncached<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                id: Self::Id,
+            ) -> sqlx::Result<Self> {
+                #query
+                    .fetch_one(db)
+                    .await
+            }
+
+            fn invalidate_cached(id: Self::Id) {
+                <Self as ormx::Cached>::invalidate(id);
+            }
+        };
+    }
 
     quote! {
         async fn get<'a, 'c: 'a>(
-            db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
             id: Self::Id,
         ) -> sqlx::Result<Self> {
-            sqlx::query_as!(Self, #get_sql, id)
+            #query
                 .fetch_one(db)
                 .await
         }
     }
 }
 
-fn update<B: Backend>(table: &Table<B>) -> TokenStream {
+pub fn update_sql<B: Backend>(table: &Table<B>) -> String {
     let mut bindings = B::Bindings::default();
     let mut assignments = vec![];
     for field in table.fields_except_id() {
@@ -76,98 +122,130 @@ fn update<B: Backend>(table: &Table<B>) -> TokenStream {
     }
     let assignments = assignments.join(", ");
 
-    let update_sql = format!(
+    format!(
         "UPDATE {} SET {} WHERE {} = {}",
         table.name(),
         assignments,
         table.id.column(),
         bindings.next().unwrap()
-    );
+    )
+}
+
+fn update<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
+    let update_sql = update_sql(table);
     let id_argument = &table.id.field;
-    let other_arguments = table.fields_except_id().map(TableField::fmt_as_argument);
+    let mut arguments = table
+        .fields_except_id()
+        .map(|field| field.fmt_as_argument(table.unchecked))
+        .collect::<Vec<_>>();
+    arguments.push(quote!(self.#id_argument));
+    let query = query(table.unchecked, &update_sql, &arguments);
+    let invalidate_cached = invalidate_cached(table);
 
     quote! {
         async fn update<'a, 'c: 'a>(
             &'a self,
-            db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
         ) -> sqlx::Result<()> {
-            sqlx::query!(#update_sql, #( #other_arguments, )* self.#id_argument)
+            #query
                 .execute(db)
                 .await?;
+            #invalidate_cached
             Ok(())
         }
     }
 }
 
-fn stream_all<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
+pub fn order_by<B: Backend>(table: &Table<B>) -> String {
+    match &table.order_by {
+        None => format!("{} DESC", table.id.column()),
+        Some(by) => by.clone(),
+    }
+}
+
+pub fn all_sql<B: Backend>(table: &Table<B>) -> String {
+    format!(
+        "SELECT {} FROM {} ORDER BY {}",
+        table.select_column_list(),
+        table.name(),
+        order_by(table)
+    )
+}
+
+pub fn all_paginated_sql<B: Backend>(table: &Table<B>) -> String {
+    let mut bindings = B::Bindings::default();
+    format!(
+        "SELECT {} FROM {} ORDER BY {} LIMIT {} OFFSET {} ",
+        table.select_column_list(),
+        table.name(),
+        order_by(table),
+        bindings.next().unwrap(),
+        bindings.next().unwrap()
+    )
+}
+
+fn stream_all<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
     let return_type = crate::utils::stream!(sqlx::Result<Self>);
-    let order_by = match &table.order_by {
-        None => &format!("{} DESC", table.id.column()),
-        Some(by) => by,
-    };
-    let all_sql = format!(
-        "SELECT {} FROM {} ORDER BY {order_by}",
-        column_list,
-        table.name()
-    );
+    let all_sql = all_sql(table);
+    let query = query_as(&table.rows(), &all_sql, &[]);
 
     quote! {
         fn stream_all<'a, 'c: 'a>(
-            db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
         ) -> #return_type + 'a {
-            sqlx::query_as!(Self, #all_sql)
+            #query
                 .fetch(db)
         }
     }
 }
 
-fn stream_all_paginated<B: Backend>(table: &Table<B>, column_list: &str) -> TokenStream {
+fn stream_all_paginated<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
     let return_type = crate::utils::stream!(sqlx::Result<Self>);
-    let mut bindings = B::Bindings::default();
-    let order_by = match &table.order_by {
-        None => &format!("{} DESC", table.id.column()),
-        Some(by) => by,
-    };
-    let all_sql = format!(
-        "SELECT {} FROM {} ORDER BY {order_by} LIMIT {} OFFSET {} ",
-        column_list,
-        table.name(),
-        bindings.next().unwrap(),
-        bindings.next().unwrap()
-    );
+    let all_sql = all_paginated_sql(table);
+    let query = query_as(&table.rows(), &all_sql, &[quote!(limit), quote!(offset)]);
 
     quote! {
         fn stream_all_paginated<'a, 'c: 'a>(
-            db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
             offset: i64,
             limit: i64,
         ) -> #return_type + 'a {
-            sqlx::query_as!(Self, #all_sql, limit, offset)
+            #query
                 .fetch(db)
         }
     }
 }
 
-fn delete<B: Backend>(table: &Table<B>) -> TokenStream {
-    let id_ty = &table.id.ty;
-    let delete_sql = format!(
+pub fn delete_sql<B: Backend>(table: &Table<B>) -> String {
+    format!(
         "DELETE FROM {} WHERE {} = {}",
         table.name(),
         table.id.column(),
         B::Bindings::default().next().unwrap()
-    );
+    )
+}
+
+fn delete<B: Backend>(table: &Table<B>) -> TokenStream {
+    let db = B::database();
+    let id_ty = &table.id.ty;
+    let delete_sql = delete_sql(table);
     let query_result = B::query_result();
+    let query = query(table.unchecked, &delete_sql, &[quote!(id)]);
 
     quote! {
         async fn delete_row<'a, 'c: 'a>(
-            db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+            db: impl sqlx::Executor<'c, Database = #db> + 'a,
             id: #id_ty
         ) -> sqlx::Result<()> {
             use #query_result;
 
-            let result = sqlx::query!(#delete_sql, id)
+            let result = #query
                 .execute(db)
                 .await?;
+            <Self as ormx::Table>::invalidate_cached(id);
             if result.rows_affected() == 0 {
                 Err(sqlx::Error::RowNotFound)
             } else {
@@ -0,0 +1,79 @@
+//! Inference of SQL types from the Rust types of fields
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{GenericArgument, PathArguments, Type};
+
+/// If the type is an `Option<T>`, returns `T`.
+pub fn option_inner(ty: &Type) -> Option<&Type> {
+    match generic_argument(ty)? {
+        (outer, inner) if outer == "Option" => Some(inner),
+        _ => None,
+    }
+}
+
+/// Returns the name of the type and its first generic argument, e.g `("Vec", u8)` for `Vec<u8>`.
+fn generic_argument(ty: &Type) -> Option<(String, &Type)> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some((segment.ident.to_string(), inner)),
+        _ => None,
+    }
+}
+
+/// Infers the SQL type from the Rust type of a field, based on its name.
+pub fn infer_sql_type(ty: &Type) -> Option<TokenStream> {
+    if let Some((outer, inner)) = generic_argument(ty) {
+        let inner = type_name(inner);
+        return match (outer.as_str(), inner.as_deref()) {
+            ("Vec", Some("u8")) => Some(quote!(ormx::ddl::SqlType::Bytes)),
+            ("Json", _) => Some(quote!(ormx::ddl::SqlType::Json)),
+            ("DateTime", _) => Some(quote!(ormx::ddl::SqlType::TimestampTz)),
+            _ => None,
+        };
+    }
+
+    let sql_type = match type_name(ty)?.as_str() {
+        "bool" => quote!(Boolean),
+        "i8" => quote!(TinyInt),
+        "i16" => quote!(SmallInt),
+        "i32" => quote!(Integer),
+        "i64" => quote!(BigInt),
+        "u8" => quote!(UnsignedTinyInt),
+        "u16" => quote!(UnsignedSmallInt),
+        "u32" => quote!(UnsignedInteger),
+        "u64" => quote!(UnsignedBigInt),
+        "f32" => quote!(Real),
+        "f64" => quote!(Double),
+        "Decimal" | "BigDecimal" => quote!(Decimal),
+        "String" => quote!(Text),
+        "Uuid" => quote!(Uuid),
+        "Value" | "JsonValue" => quote!(Json),
+        "NaiveDate" | "Date" => quote!(Date),
+        "NaiveTime" | "Time" => quote!(Time),
+        "NaiveDateTime" | "PrimitiveDateTime" => quote!(Timestamp),
+        "OffsetDateTime" => quote!(TimestampTz),
+        _ => return None,
+    };
+    Some(quote!(ormx::ddl::SqlType::#sql_type))
+}
+
+/// Returns true if the type is a primitive integer.
+#[cfg(feature = "mysql")]
+pub fn is_integer(ty: &Type) -> bool {
+    matches!(
+        type_name(ty).as_deref(),
+        Some("i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "isize" | "usize")
+    )
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => Some(path.path.segments.last()?.ident.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,133 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse_quote;
+
+use super::{field_getters, from_row, query, query_as, query_scalar, Rows};
+use crate::{backend::Backend, table::TableField, view::View};
+
+/// Generates an `impl <View>` block with read-only accessors.
+pub fn impl_view<B: Backend>(view: &View<B>) -> TokenStream {
+    let db = B::database();
+    let ident = &view.ident;
+    let vis = &view.vis;
+    let (impl_generics, ty_generics, where_clause) = view.generics.split_for_impl();
+    let return_type = crate::utils::stream!(sqlx::Result<Self>);
+    let column_list = view.select_column_list();
+    let name = view.name();
+    let order_by = match &view.order_by {
+        Some(by) => format!(" ORDER BY {by}"),
+        None => String::new(),
+    };
+
+    let all_sql = format!("SELECT {column_list} FROM {name}{order_by}");
+    let mut bindings = B::Bindings::default();
+    let all_paginated_sql = format!(
+        "SELECT {column_list} FROM {name}{order_by} LIMIT {} OFFSET {}",
+        bindings.next().unwrap(),
+        bindings.next().unwrap()
+    );
+    let count_sql = format!("SELECT COUNT(*) AS {} FROM {name}", B::quote("count!"));
+    let rows = Rows::new(view.unchecked);
+    let getters = field_getters(
+        vis,
+        &name,
+        &column_list,
+        &view.fields,
+        &rows,
+        TableField::column,
+    );
+    let refresh = if view.materialized {
+        refresh::<B>(vis, &name, view.unchecked)
+    } else {
+        quote!()
+    };
+
+    let all = query_as(&rows, &all_sql, &[]);
+    let all_paginated = query_as(&rows, &all_paginated_sql, &[quote!(limit), quote!(offset)]);
+    let count = query_scalar(view.unchecked, &parse_quote!(i64), &count_sql, &[]);
+    let from_row = from_row(view.unchecked, ident, &view.generics, &view.fields, &[]);
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Streams all rows of this view.
+            #vis fn stream_all<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+            ) -> #return_type + 'a {
+                #all
+                    .fetch(db)
+            }
+
+            /// Streams at most `limit` rows of this view, skipping the first `offset` rows.
+            #vis fn stream_all_paginated<'a, 'c: 'a>(
+                db: impl sqlx::Executor<'c, Database = #db> + 'a,
+                offset: i64,
+                limit: i64,
+            ) -> #return_type + 'a {
+                #all_paginated
+                    .fetch(db)
+            }
+
+            /// Loads all rows of this view.
+            #vis async fn all(
+                db: impl sqlx::Executor<'_, Database = #db>,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all(db).try_collect().await
+            }
+
+            /// Loads at most `limit` rows of this view, skipping the first `offset` rows.
+            #vis async fn all_paginated(
+                db: impl sqlx::Executor<'_, Database = #db>,
+                offset: i64,
+                limit: i64,
+            ) -> sqlx::Result<Vec<Self>> {
+                use ormx::exports::TryStreamExt;
+
+                Self::stream_all_paginated(db, offset, limit)
+                    .try_collect()
+                    .await
+            }
+
+            /// Counts the rows of this view.
+            #vis async fn count(
+                db: impl sqlx::Executor<'_, Database = #db>,
+            ) -> sqlx::Result<i64> {
+                #count
+                    .fetch_one(db)
+                    .await
+            }
+
+            #getters
+            #refresh
+        }
+
+        #from_row
+    }
+}
+
+fn refresh<B: Backend>(vis: &syn::Visibility, name: &str, unchecked: bool) -> TokenStream {
+    let db = B::database();
+    let refresh = query(unchecked, &format!("REFRESH MATERIALIZED VIEW {name}"), &[]);
+    let refresh_concurrently = query(
+        unchecked,
+        &format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {name}"),
+        &[],
+    );
+
+    quote! {
+        /// Refreshes this materialized view.
+        /// Refreshing it concurrently does not lock out readers, but requires a unique index.
+        #vis async fn refresh(
+            db: impl sqlx::Executor<'_, Database = #db>,
+            concurrently: bool,
+        ) -> sqlx::Result<()> {
+            if concurrently {
+                #refresh_concurrently.execute(db).await?;
+            } else {
+                #refresh.execute(db).await?;
+            }
+            Ok(())
+        }
+    }
+}
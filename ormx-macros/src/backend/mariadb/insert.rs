@@ -4,11 +4,16 @@ use quote::quote;
 use syn::Ident;
 
 use crate::{
-    backend::mariadb::{MariaBackend, MariaBindings},
+    backend::{
+        common::query,
+        mariadb::{MariaBackend, MariaBindings},
+        Backend,
+    },
     table::{Table, TableField},
 };
 
 pub fn impl_insert(table: &Table<MariaBackend>) -> TokenStream {
+    let db = MariaBackend::database();
     let insert_ident = match &table.insertable {
         Some(i) => &i.ident,
         None => return quote!(),
@@ -18,13 +23,19 @@ pub fn impl_insert(table: &Table<MariaBackend>) -> TokenStream {
     let default_fields: Vec<&TableField<MariaBackend>> = table.default_fields().collect();
 
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
     let insert_field_idents = insert_fields.iter().map(|field| &field.field);
     let default_field_idents = default_fields.iter().map(|field| &field.field);
+    let skipped_idents = table.skipped.iter().map(|field| &field.field);
     let default_field_ordinals = (0usize..).take(default_fields.len());
 
     let insert_sql = insert_sql(table, &insert_fields);
 
-    let insert_field_exprs = insert_fields.iter().map(|f| f.fmt_as_argument());
+    let insert_field_exprs = insert_fields
+        .iter()
+        .map(|f| f.fmt_as_argument(table.unchecked))
+        .collect::<Vec<_>>();
+    let query = query(table.unchecked, &insert_sql, &insert_field_exprs);
 
     let fetch_fn = if default_fields.is_empty() {
         Ident::new("execute", Span::call_site())
@@ -33,22 +44,24 @@ pub fn impl_insert(table: &Table<MariaBackend>) -> TokenStream {
     };
 
     quote! {
-        impl ormx::Insert for #insert_ident {
-            type Table = #table_ident;
+        impl #impl_generics ormx::Insert for #insert_ident #ty_generics #where_clause {
+            type Table = #table_ident #ty_generics;
 
             async fn insert<'a, 'c: 'a>(
                 self,
-                db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+                db: impl sqlx::Acquire<'c, Database = #db> + Send + 'a,
             ) -> sqlx::Result<Self::Table> {
                 use sqlx::Row;
 
-                let _generated = sqlx::query!(#insert_sql, #( #insert_field_exprs, )*)
-                    .#fetch_fn(db)
+                let mut conn = sqlx::Acquire::acquire(db).await?;
+                let _generated = #query
+                    .#fetch_fn(&mut *conn)
                     .await?;
 
                 Ok(Self::Table {
                     #( #insert_field_idents: self.#insert_field_idents, )*
                     #( #default_field_idents: _generated.get(#default_field_ordinals), )*
+                    #( #skipped_idents: self.#skipped_idents, )*
                 })
             }
         }
@@ -60,7 +73,7 @@ fn insert_sql(table: &Table<MariaBackend>, insert_fields: &[&TableField<MariaBac
     let fields = MariaBindings.take(insert_fields.len()).join(", ");
     let returning_fields = table
         .default_fields()
-        .map(TableField::fmt_for_select)
+        .map(|field| field.fmt_for_select(table.unchecked))
         .join(", ");
 
     if returning_fields.is_empty() {
@@ -12,8 +12,14 @@ pub struct MariaBackend;
 
 impl Backend for MariaBackend {
     const QUOTE: char = '`';
+    const RETURNING: bool = true;
+    const FOR_SHARE: &'static str = "LOCK IN SHARE MODE";
     type Bindings = MariaBindings;
 
+    fn database() -> TokenStream {
+        quote!(sqlx::MySql)
+    }
+
     fn query_result() -> TokenStream {
         quote!(sqlx::mysql::MySqlQueryResult)
     }
@@ -1,31 +1,171 @@
 use std::borrow::Cow;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::{Attribute, DeriveInput, Error, Ident, Meta, Result};
 
-use crate::{patch::Patch, table::Table};
+use crate::{
+    joined::Joined,
+    patch::Patch,
+    projection::Projection,
+    table::Table,
+    utils::{set_once, unknown},
+    view::View,
+};
 
 mod common;
+pub use common::Rows;
 #[cfg(feature = "mariadb")]
-mod mariadb;
+pub mod mariadb;
 #[cfg(feature = "mysql")]
-mod mysql;
+pub mod mysql;
 #[cfg(feature = "postgres")]
-mod postgres;
+pub mod postgres;
 
-#[cfg(feature = "mariadb")]
-pub type Implementation = mariadb::MariaBackend;
-#[cfg(feature = "mysql")]
-pub type Implementation = mysql::MySqlBackend;
-#[cfg(feature = "postgres")]
-pub type Implementation = postgres::PgBackend;
 #[cfg(feature = "sqlite")]
 compile_error!("sqlite is currently not supported");
 
+/// A backend enabled by a feature.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum BackendKind {
+    #[cfg(feature = "postgres")]
+    Postgres,
+    #[cfg(feature = "mysql")]
+    MySql,
+    #[cfg(feature = "mariadb")]
+    MariaDb,
+}
+
+impl BackendKind {
+    const ENABLED: &'static [BackendKind] = &[
+        #[cfg(feature = "postgres")]
+        BackendKind::Postgres,
+        #[cfg(feature = "mysql")]
+        BackendKind::MySql,
+        #[cfg(feature = "mariadb")]
+        BackendKind::MariaDb,
+    ];
+
+    const ALL: &'static [&'static str] = &["postgres", "mysql", "mariadb"];
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "postgres")]
+            BackendKind::Postgres => "postgres",
+            #[cfg(feature = "mysql")]
+            BackendKind::MySql => "mysql",
+            #[cfg(feature = "mariadb")]
+            BackendKind::MariaDb => "mariadb",
+        }
+    }
+
+    fn from_ident(ident: &Ident) -> Result<Self> {
+        let name = ident.to_string();
+        if let Some(backend) = Self::ENABLED.iter().find(|b| b.name() == name) {
+            Ok(*backend)
+        } else if Self::ALL.contains(&&*name) {
+            Err(Error::new(
+                ident.span(),
+                format!("the `{name}` feature of ormx is not enabled"),
+            ))
+        } else {
+            Err(unknown("backend", ident, Self::ALL))
+        }
+    }
+
+    /// Selects the backend given by `#[ormx(backend = ..)]`, or the only enabled backend.
+    pub fn select(input: &DeriveInput) -> Result<Self> {
+        match backend_attr(&input.attrs)? {
+            Some(backend) => Self::from_ident(&backend),
+            None => match Self::ENABLED {
+                [backend] => Ok(*backend),
+                _ => Err(Error::new(
+                    input.ident.span(),
+                    "multiple backends are enabled, select one using #[ormx(backend = ..)]",
+                )),
+            },
+        }
+    }
+}
+
+/// Finds `backend = <ident>` in the `#[ormx(..)]` attributes.
+/// This has to happen before the attributes are parsed, since parsing depends on the backend.
+fn backend_attr(attrs: &[Attribute]) -> Result<Option<Ident>> {
+    let mut backend = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("ormx")) {
+        let Meta::List(list) = &attr.meta else {
+            continue;
+        };
+        let tokens = list.tokens.clone().into_iter().collect::<Vec<_>>();
+        for (i, window) in tokens.windows(3).enumerate() {
+            let at_start = match i.checked_sub(1).map(|i| &tokens[i]) {
+                None => true,
+                Some(TokenTree::Punct(p)) => p.as_char() == ',',
+                Some(_) => false,
+            };
+            if let [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Ident(value)] = window {
+                if at_start && key == "backend" && eq.as_char() == '=' {
+                    set_once(&mut backend, value.clone(), key.span())?;
+                }
+            }
+        }
+    }
+    Ok(backend)
+}
+
+/// Calls a generic function with the given `BackendKind` as type parameter, e.g
+/// `dispatch!(kind => derive(input))` calls `derive::<PgBackend>(input)` for PostgreSQL.
+macro_rules! dispatch {
+    ($kind:expr => $f:ident ( $($arg:expr),* )) => {
+        match $kind {
+            #[cfg(feature = "postgres")]
+            $crate::backend::BackendKind::Postgres => {
+                $f::<$crate::backend::postgres::PgBackend>($($arg),*)
+            }
+            #[cfg(feature = "mysql")]
+            $crate::backend::BackendKind::MySql => {
+                $f::<$crate::backend::mysql::MySqlBackend>($($arg),*)
+            }
+            #[cfg(feature = "mariadb")]
+            $crate::backend::BackendKind::MariaDb => {
+                $f::<$crate::backend::mariadb::MariaBackend>($($arg),*)
+            }
+        }
+    };
+}
+pub(crate) use dispatch;
+
 pub trait Backend: Sized + Clone {
     const QUOTE: char;
+    /// Whether `INSERT .. RETURNING` is supported, which returns database-generated columns
+    const RETURNING: bool;
+    /// Clause of a `SELECT` locking the selected rows in shared mode
+    const FOR_SHARE: &'static str = "FOR SHARE";
+    /// Whether `UPDATE .. RETURNING` is supported
+    const UPDATE_RETURNING: bool = false;
+    /// Whether materialized views are supported
+    const MATERIALIZED_VIEWS: bool = false;
 
     type Bindings: Iterator<Item = Cow<'static, str>> + Default;
 
+    /// Quotes a single identifier, e.g a column, table or schema name
+    fn quote(ident: &str) -> String {
+        let q = Self::QUOTE;
+        let escaped = ident.replace(q, &format!("{q}{q}"));
+        format!("{q}{escaped}{q}")
+    }
+
+    /// Quotes a table name, optionally qualified by a schema
+    fn quote_table(schema: Option<&str>, table: &str) -> String {
+        match schema {
+            Some(schema) => format!("{}.{}", Self::quote(schema), Self::quote(table)),
+            None => Self::quote(table),
+        }
+    }
+
+    /// Returns the sqlx database type, e.g `sqlx::Postgres`
+    fn database() -> TokenStream;
+
     /// Returns the type used to return query results, e.g `sqlx::postgres::PgQueryResult`
     fn query_result() -> TokenStream;
 
@@ -39,6 +179,69 @@ pub trait Backend: Sized + Clone {
         common::setters::<Self>(table)
     }
 
+    /// Generate the `<Table>Column` enum
+    fn impl_column_enum(table: &Table<Self>) -> TokenStream {
+        common::column_enum::<Self>(table)
+    }
+
+    /// Generate the `<Table>Sort` enum and an `impl <Table>` block, containing sorted accessors
+    fn impl_sort(table: &Table<Self>) -> TokenStream {
+        common::sort::<Self>(table)
+    }
+
+    /// Generate an `impl <Table>` block, containing `increment_{field}` and `decrement_{field}`
+    /// for every field using `#[ormx(counter)]`
+    fn impl_counters(table: &Table<Self>) -> TokenStream {
+        common::counters::<Self>(table)
+    }
+
+    /// Generate an `impl <Table>` block containing `for_each_batch`
+    fn impl_batches(table: &Table<Self>) -> TokenStream {
+        common::batches::<Self>(table)
+    }
+
+    /// Generate an `impl <Table>` block for `derive(Queue)`, containing `dequeue`, `complete`,
+    /// `fail` and `requeue_stale`
+    fn impl_queue(_table: &Table<Self>) -> TokenStream {
+        Error::new(
+            Span::call_site(),
+            "derive(Queue) is only supported by the postgres backend",
+        )
+        .to_compile_error()
+    }
+
+    /// Generate an `impl <Table>` block, containing the locking variants of `get` and the getters
+    fn impl_locks(table: &Table<Self>) -> TokenStream {
+        common::locks::<Self>(table)
+    }
+
+    /// Generate an `impl <Table>` block, containing `changes` and `notify_trigger_sql`
+    fn impl_notify(table: &Table<Self>) -> TokenStream {
+        match table.notify {
+            Some(_) => Error::new(
+                Span::call_site(),
+                "#[ormx(notify)] is only supported by the postgres backend",
+            )
+            .to_compile_error(),
+            None => quote!(),
+        }
+    }
+
+    /// Generate an `impl <Table>` block, containing `create_table_sql`
+    fn impl_ddl(table: &Table<Self>) -> TokenStream {
+        common::ddl::<Self>(table)
+    }
+
+    /// Generate an `impl TableMeta for <Table>` block and register the table for verification
+    fn impl_table_meta(table: &Table<Self>) -> TokenStream {
+        common::impl_table_meta::<Self>(table)
+    }
+
+    /// Generate an `impl Cached for <Table>` block if the table uses `#[ormx(cache)]`
+    fn impl_cache(table: &Table<Self>) -> TokenStream {
+        common::impl_cache::<Self>(table)
+    }
+
     /// Generate an `impl Table for <Table>` block
     fn impl_table(table: &Table<Self>) -> TokenStream {
         common::impl_table::<Self>(table)
@@ -61,4 +264,19 @@ pub trait Backend: Sized + Clone {
     fn impl_patch(patch: &Patch) -> TokenStream {
         common::impl_patch::<Self>(patch)
     }
+
+    /// Generate an `impl <Projection>` block, containing accessors selecting only its columns
+    fn impl_projection(projection: &Projection<Self>) -> TokenStream {
+        common::impl_projection::<Self>(projection)
+    }
+
+    /// Generate an `impl <Joined>` block, containing accessors selecting from all joined tables
+    fn impl_joined(joined: &Joined<Self>) -> TokenStream {
+        common::impl_joined::<Self>(joined)
+    }
+
+    /// Generate an `impl <View>` block, containing read-only accessors
+    fn impl_view(view: &View<Self>) -> TokenStream {
+        common::impl_view::<Self>(view)
+    }
 }
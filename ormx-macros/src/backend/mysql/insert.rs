@@ -1,37 +1,88 @@
 use itertools::Itertools;
-use proc_macro2::TokenStream;
-use quote::quote;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Error, Ident, Result};
 
 use super::MySqlBackend;
 use crate::{
-    backend::mysql::MySqlBindings,
+    backend::{
+        common::{get_column, is_integer, query, query_scalar},
+        mysql::MySqlBindings,
+        Backend,
+    },
     table::{Table, TableField},
 };
 
+/// How the ID of an inserted row is obtained.
+enum IdSource<'a> {
+    /// The ID is part of the insert struct.
+    Given,
+    /// The ID is an `AUTO_INCREMENT` integer, queried using `LAST_INSERT_ID()`.
+    LastInsertId,
+    /// The ID is generated by evaluating `#[ormx(generate)]` before the insert, and then inserted
+    /// explicitly.
+    PreSelect(String),
+    /// The ID is generated by the database (e.g by a trigger), and looked up after the insert
+    /// using `#[ormx(lookup)]`.
+    Lookup(&'a TableField<MySqlBackend>),
+}
+
+impl<'a> IdSource<'a> {
+    fn of(table: &'a Table<MySqlBackend>) -> Result<Self> {
+        let id = &table.id;
+        if !id.default {
+            return Ok(IdSource::Given);
+        }
+        if let Some(expr) = &id.generate {
+            return Ok(IdSource::PreSelect(expr.value()));
+        }
+        if let Some(lookup) = &id.lookup {
+            let field = table.fields.iter().find(|field| field.field == *lookup);
+            return Ok(IdSource::Lookup(
+                field.expect("checked when parsing the table"),
+            ));
+        }
+        if is_integer(&id.ty) {
+            return Ok(IdSource::LastInsertId);
+        }
+        Err(Error::new(
+            id.field.span(),
+            "only an integer ID can be obtained using LAST_INSERT_ID(), \
+             use #[ormx(generate = \"..\")] or #[ormx(lookup = ..)] to obtain this ID",
+        ))
+    }
+}
+
 pub fn impl_insert(table: &Table<MySqlBackend>) -> TokenStream {
+    let db = MySqlBackend::database();
     let insert_ident = match &table.insertable {
         Some(i) => &i.ident,
         None => return quote!(),
     };
 
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
 
-    let insert = insert(table);
-    let query_id = query_id(table);
+    let id_source = match IdSource::of(table) {
+        Ok(id_source) => id_source,
+        Err(err) => return err.to_compile_error(),
+    };
+    let pre_select_id = pre_select_id(table, &id_source);
+    let insert = insert(table, &id_source);
+    let query_id = query_id(table, &id_source);
     let query_default = query_default(table);
-    let construct_row = construct_row(table);
+    let construct_row = construct_row(table, &id_source);
 
     quote! {
-        impl ormx::Insert for #insert_ident {
-            type Table = #table_ident;
+        impl #impl_generics ormx::Insert for #insert_ident #ty_generics #where_clause {
+            type Table = #table_ident #ty_generics;
 
             async fn insert<'a, 'c: 'a>(
                 self,
-                db: &'c mut sqlx::MySqlConnection,
+                db: impl sqlx::Acquire<'c, Database = #db> + Send + 'a,
             ) -> sqlx::Result<Self::Table> {
-                use sqlx::Connection;
-
-                let mut tx = db.begin().await?;
+                let mut tx = sqlx::Acquire::begin(db).await?;
+                #pre_select_id
                 #insert
                 #query_id
                 #query_default
@@ -43,25 +94,37 @@ pub fn impl_insert(table: &Table<MySqlBackend>) -> TokenStream {
 }
 
 /// build an instance of the table struct from
-/// - `_id` (see `query_id` below)
+/// - `_id` (see `pre_select_id` and `query_id` below)
 /// - `_generated` (see `query_default` below)
-/// - all fields already present in the insert struct
-fn construct_row(table: &Table<MySqlBackend>) -> TokenStream {
+/// - all fields already present in the insert struct, including skipped fields
+fn construct_row(table: &Table<MySqlBackend>, id_source: &IdSource) -> TokenStream {
     let id_ident = &table.id.field;
+    let id_value = match id_source {
+        // `LAST_INSERT_ID()` is always an u64
+        IdSource::LastInsertId => quote!(_id as _),
+        _ => quote!(_id),
+    };
     let insert_field_idents = table
         .insertable_fields()
         .map(|f| &f.field)
         .filter(|f| *f != id_ident);
-    let default_field_idents = table
+    let default_fields = table
         .default_fields()
-        .map(|f| &f.field)
-        .filter(|f| *f != id_ident);
+        .filter(|f| f.field != *id_ident)
+        .collect::<Vec<_>>();
+    let default_field_idents = default_fields.iter().map(|f| &f.field);
+    let skipped_idents = table.skipped.iter().map(|f| &f.field);
+    let generated = Ident::new("_generated", Span::call_site());
+    let default_field_values = default_fields
+        .iter()
+        .map(|f| get_column(table.unchecked, &generated, f));
 
     quote! {
         Self::Table {
-            #id_ident: _id as _,
+            #id_ident: #id_value,
             #( #insert_field_idents: self.#insert_field_idents, )*
-            #( #default_field_idents: _generated.#default_field_idents, )*
+            #( #default_field_idents: #default_field_values, )*
+            #( #skipped_idents: self.#skipped_idents, )*
         }
     }
 }
@@ -79,32 +142,72 @@ fn query_default(table: &Table<MySqlBackend>) -> TokenStream {
 
     let query_default_sql = format!(
         "SELECT {} FROM {} WHERE {} = ?",
-        default_fields.map(TableField::fmt_for_select).join(", "),
+        default_fields
+            .map(|field| field.fmt_for_select(table.unchecked))
+            .join(", "),
         table.name(),
         table.id.column()
     );
 
+    let query = query(table.unchecked, &query_default_sql, &[quote!(_id)]);
+
     quote! {
-        let _generated = sqlx::query!(#query_default_sql, _id)
+        let _generated = #query
+            .fetch_one(&mut *tx)
+            .await?;
+    }
+}
+
+/// generates the ID before the insert by evaluating an SQL expression, e.g `UUID()`.
+fn pre_select_id(table: &Table<MySqlBackend>, id_source: &IdSource) -> TokenStream {
+    let IdSource::PreSelect(expr) = id_source else {
+        return quote!();
+    };
+
+    let sql = format!("SELECT {} AS {}", expr, id_alias(table));
+    let query = query_scalar(table.unchecked, &table.id.ty, &sql, &[]);
+    quote! {
+        let _id = #query
             .fetch_one(&mut *tx)
             .await?;
     }
 }
 
 /// inserts the struct into the database
-fn insert(table: &Table<MySqlBackend>) -> TokenStream {
+fn insert(table: &Table<MySqlBackend>, id_source: &IdSource) -> TokenStream {
     let insert_fields: Vec<_> = table.insertable_fields().collect();
-    let insert_field_idents = insert_fields.iter().map(|field| &field.field);
+    let mut columns = insert_fields
+        .iter()
+        .map(|field| field.column())
+        .collect::<Vec<_>>();
+    let mut insert_field_exprs = insert_fields
+        .iter()
+        .map(|field| field.fmt_as_argument(table.unchecked))
+        .collect::<Vec<_>>();
+
+    if let IdSource::PreSelect(_) = id_source {
+        let id_ty = &table.id.ty;
+        columns.insert(0, table.id.column());
+        insert_field_exprs.insert(
+            0,
+            match table.id.custom_type && !table.unchecked {
+                true => quote!(_id as #id_ty),
+                false => quote!(_id),
+            },
+        );
+    }
 
     let insert_sql = format!(
         "INSERT INTO {} ({}) VALUES ({})",
         table.name(),
-        insert_fields.iter().map(|field| field.column()).join(", "),
-        MySqlBindings.take(insert_fields.len()).join(", ")
+        columns.join(", "),
+        MySqlBindings.take(columns.len()).join(", ")
     );
 
+    let query = query(table.unchecked, &insert_sql, &insert_field_exprs);
+
     quote! {
-        sqlx::query!(#insert_sql, #( self.#insert_field_idents, )*)
+        #query
             .execute(&mut *tx)
             .await?;
     }
@@ -113,20 +216,61 @@ fn insert(table: &Table<MySqlBackend>) -> TokenStream {
 /// obtains the id of the inserted row.
 ///
 /// case 1:
-///     The ID is database generated, so we query it with LAST_INSERT_ID
+///     The ID is an `AUTO_INCREMENT` integer, so we query it with LAST_INSERT_ID
 /// case 2:
-///     The ID is already known, so we can just use it.
-fn query_id(table: &Table<MySqlBackend>) -> TokenStream {
-    match table.id.default {
-        true => quote! {
-            let _id = sqlx::query!("SELECT LAST_INSERT_ID() AS id")
-                .fetch_one(&mut *tx)
-                .await?
-                .id;
-        },
-        false => {
+///     The ID is generated by the database, so we look it up using a unique field.
+/// case 3:
+///     The ID is already known, either from the insert struct or from `pre_select_id`.
+fn query_id(table: &Table<MySqlBackend>, id_source: &IdSource) -> TokenStream {
+    match id_source {
+        IdSource::LastInsertId => {
+            let query = query_scalar(
+                table.unchecked,
+                &parse_quote!(u64),
+                "SELECT LAST_INSERT_ID()",
+                &[],
+            );
+            quote! {
+                let _id = #query
+                    .fetch_one(&mut *tx)
+                    .await?;
+            }
+        }
+        IdSource::Lookup(field) => {
+            let sql = format!(
+                "SELECT {} AS {} FROM {} WHERE {} = ?",
+                table.id.column(),
+                id_alias(table),
+                table.name(),
+                field.column()
+            );
+            let query = query_scalar(
+                table.unchecked,
+                &table.id.ty,
+                &sql,
+                &[field.fmt_as_argument(table.unchecked)],
+            );
+            quote! {
+                let _id = #query
+                    .fetch_one(&mut *tx)
+                    .await?;
+            }
+        }
+        IdSource::Given => {
             let id_ident = &table.id.field;
             quote!(let _id = self.#id_ident;)
         }
+        IdSource::PreSelect(_) => quote!(),
+    }
+}
+
+/// alias of the selected ID, overriding its type when the query is checked.
+fn id_alias(table: &Table<MySqlBackend>) -> String {
+    match table.unchecked {
+        true => MySqlBackend::quote("_id"),
+        false => {
+            let ty = &table.id.ty;
+            MySqlBackend::quote(&format!("_id!: {}", ty.to_token_stream()))
+        }
     }
 }
@@ -12,8 +12,13 @@ pub struct MySqlBackend;
 
 impl Backend for MySqlBackend {
     const QUOTE: char = '`';
+    const RETURNING: bool = false;
     type Bindings = MySqlBindings;
 
+    fn database() -> TokenStream {
+        quote!(sqlx::MySql)
+    }
+
     fn query_result() -> TokenStream {
         quote!(sqlx::mysql::MySqlQueryResult)
     }
@@ -4,11 +4,16 @@ use quote::quote;
 use syn::Ident;
 
 use crate::{
-    backend::postgres::{PgBackend, PgBindings},
+    backend::{
+        common::{get_column, query},
+        postgres::{PgBackend, PgBindings},
+        Backend,
+    },
     table::{Table, TableField},
 };
 
 pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
+    let db = PgBackend::database();
     let insert_ident = match &table.insertable {
         Some(i) => &i.ident,
         None => return quote!(),
@@ -18,44 +23,97 @@ pub fn impl_insert(table: &Table<PgBackend>) -> TokenStream {
     let default_fields: Vec<&TableField<PgBackend>> = table.default_fields().collect();
 
     let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
     let insert_field_idents = insert_fields.iter().map(|field| &field.field);
     let default_field_idents = default_fields.iter().map(|field| &field.field);
+    let skipped_idents = table.skipped.iter().map(|field| &field.field);
+    let generated = Ident::new("_generated", Span::call_site());
+    let default_field_values = default_fields
+        .iter()
+        .map(|field| get_column(table.unchecked, &generated, field));
     let insert_sql = insert_sql(table, &insert_fields);
-    let insert_field_exprs = insert_fields.iter().map(|f| f.fmt_as_argument());
+    let insert_field_exprs = insert_fields
+        .iter()
+        .map(|f| f.fmt_as_argument(table.unchecked))
+        .collect::<Vec<_>>();
+    let query = query(table.unchecked, &insert_sql, &insert_field_exprs);
 
     let fetch_fn = if default_fields.is_empty() {
         Ident::new("execute", Span::call_site())
     } else {
         Ident::new("fetch_one", Span::call_site())
     };
+    let copy_in = copy_in(table, &insert_fields);
 
     quote! {
-        impl ormx::Insert for #insert_ident {
-            type Table = #table_ident;
+        #copy_in
+
+        impl #impl_generics ormx::Insert for #insert_ident #ty_generics #where_clause {
+            type Table = #table_ident #ty_generics;
 
             async fn insert<'a, 'c: 'a>(
                 self,
-                db: impl sqlx::Executor<'c, Database = ormx::Db> + 'a,
+                db: impl sqlx::Acquire<'c, Database = #db> + Send + 'a,
             ) -> sqlx::Result<Self::Table> {
-                let _generated = sqlx::query!(#insert_sql, #( #insert_field_exprs, )*)
-                    .#fetch_fn(db)
+                let mut conn = sqlx::Acquire::acquire(db).await?;
+                let _generated = #query
+                    .#fetch_fn(&mut *conn)
                     .await?;
 
                 Ok(Self::Table {
                     #( #insert_field_idents: self.#insert_field_idents, )*
-                    #( #default_field_idents: _generated.#default_field_idents, )*
+                    #( #default_field_idents: #default_field_values, )*
+                    #( #skipped_idents: self.#skipped_idents, )*
                 })
             }
         }
     }
 }
 
+/// Generates `copy_in`, which inserts many rows at once using a binary `COPY`.
+fn copy_in(table: &Table<PgBackend>, insert_fields: &[&TableField<PgBackend>]) -> TokenStream {
+    let db = PgBackend::database();
+    let vis = &table.vis;
+    let insert_ident = &table.insertable.as_ref().unwrap().ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+
+    let columns = insert_fields.iter().map(|field| field.column()).join(", ");
+    let sql = format!(
+        "COPY {} ({}) FROM STDIN (FORMAT binary)",
+        table.name(),
+        columns
+    );
+    let select_sql = format!("SELECT {} FROM {}", columns, table.name());
+    let field_count = insert_fields.len() as i16;
+    let field_idents = insert_fields.iter().map(|field| &field.field);
+
+    quote! {
+        impl #impl_generics #insert_ident #ty_generics #where_clause {
+            /// Inserts all rows using `COPY .. FROM STDIN (FORMAT binary)`, returning the number
+            /// of rows written. Columns with a default are always set to their default.
+            #vis async fn copy_in<'c>(
+                db: impl sqlx::Acquire<'c, Database = #db>,
+                rows: impl IntoIterator<Item = Self>,
+            ) -> sqlx::Result<u64> {
+                let mut conn = sqlx::Acquire::acquire(db).await?;
+                let mut copy = ormx::exports::BinaryCopy::begin(&mut *conn, #sql, #select_sql).await?;
+                for row in rows {
+                    copy.row(#field_count);
+                    #( copy.field(&row.#field_idents)?; )*
+                    copy.flush().await?;
+                }
+                copy.finish().await
+            }
+        }
+    }
+}
+
 fn insert_sql(table: &Table<PgBackend>, insert_fields: &[&TableField<PgBackend>]) -> String {
     let columns = insert_fields.iter().map(|field| field.column()).join(", ");
     let fields = PgBindings::default().take(insert_fields.len()).join(", ");
     let returning_fields = table
         .default_fields()
-        .map(TableField::fmt_for_select)
+        .map(|field| field.fmt_for_select(table.unchecked))
         .join(", ");
 
     if returning_fields.is_empty() {
@@ -6,14 +6,23 @@ use quote::quote;
 use crate::{backend::Backend, table::Table};
 
 mod insert;
+mod notify;
+mod queue;
 
 #[derive(Clone)]
 pub struct PgBackend;
 
 impl Backend for PgBackend {
     const QUOTE: char = '"';
+    const RETURNING: bool = true;
+    const UPDATE_RETURNING: bool = true;
+    const MATERIALIZED_VIEWS: bool = true;
     type Bindings = PgBindings;
 
+    fn database() -> TokenStream {
+        quote!(sqlx::Postgres)
+    }
+
     fn query_result() -> TokenStream {
         quote!(sqlx::postgres::PgQueryResult)
     }
@@ -21,6 +30,14 @@ impl Backend for PgBackend {
     fn impl_insert(table: &Table<Self>) -> TokenStream {
         insert::impl_insert(table)
     }
+
+    fn impl_notify(table: &Table<Self>) -> TokenStream {
+        notify::impl_notify(table)
+    }
+
+    fn impl_queue(table: &Table<Self>) -> TokenStream {
+        queue::impl_queue(table)
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,81 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{
+    backend::{postgres::PgBackend, Backend},
+    table::Table,
+};
+
+/// Generates `changes` and `changes_from_pool`, streaming the changes published by the trigger
+/// created by `notify_trigger_sql`.
+pub fn impl_notify(table: &Table<PgBackend>) -> TokenStream {
+    let Some(channel) = &table.notify else {
+        return quote!();
+    };
+
+    let vis = &table.vis;
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    let trigger_sql = trigger_sql(table, channel);
+    let changes_doc = format!(
+        "Streams the rows inserted, updated or deleted in this table, as published on the \
+         channel `{channel}`.\nThe stream listens on the channel using `listener`, which is also \
+         used to query inserted and updated rows. Notifications on other channels are ignored.\n\
+         This requires the trigger created by `notify_trigger_sql`."
+    );
+    let trigger_doc = format!(
+        "Returns the SQL creating a trigger which publishes all changes of this table on the \
+         channel `{channel}`.\nIt consists of multiple statements, so it has to be executed \
+         using `sqlx::raw_sql`."
+    );
+
+    quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            #[doc = #changes_doc]
+            #vis fn changes(
+                listener: sqlx::postgres::PgListener,
+            ) -> impl ormx::exports::Stream<Item = sqlx::Result<ormx::Change<Self>>> + Send + 'static {
+                ormx::exports::changes(listener, #channel)
+            }
+
+            /// Like `changes`, but connects its own listener using a connection of `pool`, which
+            /// is held until the stream is dropped.
+            #vis fn changes_from_pool(
+                pool: &sqlx::postgres::PgPool,
+            ) -> impl ormx::exports::Stream<Item = sqlx::Result<ormx::Change<Self>>> + Send + 'static {
+                ormx::exports::changes_from_pool(pool, #channel)
+            }
+
+            #[doc = #trigger_doc]
+            #vis fn notify_trigger_sql() -> &'static str {
+                #trigger_sql
+            }
+        }
+    }
+}
+
+/// The trigger publishes the operation and the ID of the changed row, e.g `UPDATE:42`.
+fn trigger_sql(table: &Table<PgBackend>, channel: &str) -> String {
+    let name = format!("{}_notify", table.table_name());
+    let function = PgBackend::quote_table(table.schema_name(), &name);
+    let trigger = PgBackend::quote(&name);
+    let channel = channel.replace('\'', "''");
+    let id = table.id.column();
+    let table = table.name();
+
+    format!(
+        "CREATE OR REPLACE FUNCTION {function}() RETURNS trigger AS $ormx$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM pg_notify('{channel}', TG_OP || ':' || OLD.{id}::text);
+    ELSE
+        PERFORM pg_notify('{channel}', TG_OP || ':' || NEW.{id}::text);
+    END IF;
+    RETURN NULL;
+END
+$ormx$ LANGUAGE plpgsql;
+DROP TRIGGER IF EXISTS {trigger} ON {table};
+CREATE TRIGGER {trigger} AFTER INSERT OR UPDATE OR DELETE ON {table}
+    FOR EACH ROW EXECUTE FUNCTION {function}();"
+    )
+}
@@ -0,0 +1,190 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Ident, Result};
+
+use crate::{
+    backend::{
+        common::{query, query_as, query_scalar},
+        postgres::PgBackend,
+    },
+    table::{Table, TableField},
+};
+
+/// Generates `dequeue`, `complete`, `fail` and `requeue_stale` for `derive(Queue)`.
+///
+/// A job is either 'pending', 'running', 'done' or 'failed'. While it's running, `run_at` holds
+/// the time at which it has been claimed, so `complete` and `fail` can check that the job has not
+/// been requeued and claimed again in the meantime.
+pub fn impl_queue(table: &Table<PgBackend>) -> TokenStream {
+    match try_impl_queue(table) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+fn try_impl_queue(table: &Table<PgBackend>) -> Result<TokenStream> {
+    let queue = table
+        .queue
+        .as_ref()
+        .expect("derive(Queue) without #[ormx(queue(..))]");
+    let status = queue_field(table, &queue.status)?.column();
+    let run_at = queue_field(table, &queue.run_at)?;
+    let attempts_field = match &queue.attempts {
+        Some(attempts) => queue_field(table, attempts)?,
+        None => table
+            .fields
+            .iter()
+            .find(|field| field.field == "attempts")
+            .ok_or_else(|| {
+                Error::new(
+                    table.ident.span(),
+                    "derive(Queue) requires an `attempts` field, or `#[ormx(queue(attempts = ..))]`",
+                )
+            })?,
+    };
+    let attempts = attempts_field.column();
+    let attempts_ident = &attempts_field.field;
+
+    let vis = &table.vis;
+    let unchecked = table.unchecked;
+    let name = table.name();
+    let id = table.id.column();
+    let id_arg = table.id.fmt_as_argument(unchecked);
+    let run_at_arg = run_at.fmt_as_argument(unchecked);
+    let run_at = run_at.column();
+
+    let dequeue_sql = format!(
+        "UPDATE {name} SET {status} = 'running', {run_at} = now(), {attempts} = {attempts} + 1 \
+         WHERE {id} IN (SELECT {id} FROM {name} WHERE {status} = 'pending' AND {run_at} <= now() \
+         ORDER BY {run_at}, {id} LIMIT $1 FOR UPDATE SKIP LOCKED) RETURNING {}",
+        table.select_column_list()
+    );
+    let claimed = format!("WHERE {id} = $1 AND {status} = 'running' AND {run_at} = $2");
+    let complete_sql = format!("UPDATE {name} SET {status} = 'done' {claimed}");
+    let retry_sql = format!(
+        "UPDATE {name} SET {status} = 'pending', {run_at} = now() + make_interval(secs => $3) \
+         {claimed}"
+    );
+    let fail_sql = format!("UPDATE {name} SET {status} = 'failed' {claimed}");
+    // a stale job which already used up its attempts is failed, like by `fail`
+    let requeue_sql = format!(
+        "UPDATE {name} \
+         SET {status} = CASE WHEN {attempts} >= $2::int8 THEN 'failed' ELSE 'pending' END, \
+         {run_at} = now() \
+         WHERE {status} = 'running' AND {run_at} < now() - make_interval(secs => $1) \
+         RETURNING {}",
+        table.id.fmt_for_select(unchecked)
+    );
+
+    let dequeue = query_as(&table.rows(), &dequeue_sql, &[quote!(n)]);
+    let claim = [id_arg, run_at_arg];
+    let complete = query(unchecked, &complete_sql, &claim);
+    let retry = query(
+        unchecked,
+        &retry_sql,
+        &[
+            claim[0].clone(),
+            claim[1].clone(),
+            quote!(delay.as_secs_f64()),
+        ],
+    );
+    let fail = query(unchecked, &fail_sql, &claim);
+    let requeue = query_scalar(
+        unchecked,
+        &table.id.ty,
+        &requeue_sql,
+        &[
+            quote!(timeout.as_secs_f64()),
+            quote!(i64::from(retry.max_attempts())),
+        ],
+    );
+    let invalidate = table.cache.then(
+        || quote!(<Self as ormx::Table>::invalidate_cached(<Self as ormx::Table>::id(self));),
+    );
+    let invalidate_dequeued = table.cache.then(|| {
+        quote! {
+            for job in &jobs {
+                <Self as ormx::Table>::invalidate_cached(<Self as ormx::Table>::id(job));
+            }
+        }
+    });
+    let invalidate_requeued = table.cache.then(|| {
+        quote! {
+            for id in ids {
+                <Self as ormx::Table>::invalidate_cached(id);
+            }
+        }
+    });
+
+    let table_ident = &table.ident;
+    let (impl_generics, ty_generics, where_clause) = table.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #table_ident #ty_generics #where_clause {
+            /// Claims up to `n` pending jobs which are due, skipping jobs claimed concurrently.
+            /// The claimed jobs are marked as running, and their attempt is counted.
+            #vis async fn dequeue(
+                db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+                n: i64,
+            ) -> sqlx::Result<Vec<Self>> {
+                let jobs = #dequeue.fetch_all(db).await?;
+                #invalidate_dequeued
+                Ok(jobs)
+            }
+
+            /// Marks this claimed job as done.
+            /// Returns `false` if the job is no longer claimed by this worker, e.g because it has
+            /// been requeued by `requeue_stale`.
+            #vis async fn complete(
+                &self,
+                db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+            ) -> sqlx::Result<bool> {
+                let result = #complete.execute(db).await?;
+                #invalidate
+                Ok(result.rows_affected() == 1)
+            }
+
+            /// Reschedules this claimed job after the backoff of `retry`, or marks it as failed
+            /// once its attempts are exhausted.
+            /// Returns `false` if the job is no longer claimed by this worker.
+            #vis async fn fail(
+                &self,
+                db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+                retry: &ormx::Retry,
+            ) -> sqlx::Result<bool> {
+                let attempt = u32::try_from(self.#attempts_ident).unwrap_or(u32::MAX);
+                let result = match retry.backoff_after(attempt) {
+                    Some(delay) => #retry.execute(db).await?,
+                    None => #fail.execute(db).await?,
+                };
+                #invalidate
+                Ok(result.rows_affected() == 1)
+            }
+
+            /// Returns jobs which have been running for longer than `timeout` to the queue, e.g
+            /// because their worker crashed. Jobs which have used up the attempts of `retry` are
+            /// marked as failed instead.
+            /// Returns the number of requeued and failed jobs.
+            #vis async fn requeue_stale(
+                db: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+                timeout: std::time::Duration,
+                retry: &ormx::Retry,
+            ) -> sqlx::Result<u64> {
+                let ids = #requeue.fetch_all(db).await?;
+                let requeued = ids.len() as u64;
+                #invalidate_requeued
+                Ok(requeued)
+            }
+        }
+    })
+}
+
+fn queue_field<'a>(
+    table: &'a Table<PgBackend>,
+    ident: &Ident,
+) -> Result<&'a TableField<PgBackend>> {
+    table
+        .fields
+        .iter()
+        .find(|field| field.field == *ident)
+        .ok_or_else(|| Error::new(ident.span(), "does not refer to a field of the struct"))
+}
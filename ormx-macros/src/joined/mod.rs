@@ -0,0 +1,148 @@
+use std::convert::TryFrom;
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, Error, Ident, Path, Result, Visibility,
+};
+
+use crate::{
+    backend::{dispatch, Backend, BackendKind},
+    table::{callback, Table, TableField},
+};
+
+mod parse;
+
+pub struct Joined<B: Backend> {
+    pub ident: Ident,
+    pub vis: Visibility,
+    /// Path of the first table, as given by `#[ormx(from = ..)]`
+    pub from_path: Path,
+    /// The first table, followed by all joined tables
+    pub tables: Vec<Table<B>>,
+    /// The `ON` condition of every joined table
+    pub conditions: Vec<String>,
+    /// The selected fields, with the names and types of the joined struct
+    pub fields: Vec<TableField<B>>,
+    /// The (quoted) name of the table of every field
+    pub field_tables: Vec<String>,
+    pub order_by: Option<String>,
+}
+
+impl<B: Backend> Joined<B> {
+    pub fn from(&self) -> &Table<B> {
+        &self.tables[0]
+    }
+
+    /// Returns the `FROM` clause, joining all tables.
+    pub fn joined_tables(&self) -> String {
+        let mut clause = self.from().name();
+        for (table, on) in self.tables[1..].iter().zip(&self.conditions) {
+            clause += &format!(" INNER JOIN {} ON {}", table.name(), on);
+        }
+        clause
+    }
+
+    pub fn select_column_list(&self) -> String {
+        self.fields
+            .iter()
+            .zip(&self.field_tables)
+            .map(|(field, table)| field.fmt_for_select_qualified(table, self.from().unchecked))
+            .join(", ")
+    }
+
+    /// Returns the column of the given field, qualified with the name of its table.
+    pub fn qualified_column(&self, field: &TableField<B>) -> String {
+        let idx = self
+            .fields
+            .iter()
+            .position(|f| f.field == field.field)
+            .unwrap();
+        format!("{}.{}", self.field_tables[idx], field.column())
+    }
+}
+
+/// Entry point of `derive(Joined)`, which collects the definitions of all tables.
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let (from, _) = parse::table_paths(&input)?;
+    Ok(callback::with_table(
+        &from,
+        quote!(ormx::__joined),
+        quote!([] { #input }),
+    ))
+}
+
+/// Input of `ormx::__joined!`: the definition of the table which was just resolved, the
+/// definitions of all tables resolved before, and the definition of the joined struct.
+pub struct JoinedInput {
+    table: DeriveInput,
+    previous: Vec<DeriveInput>,
+    joined: DeriveInput,
+}
+
+impl Parse for JoinedInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let table;
+        syn::braced!(table in input);
+        let previous;
+        syn::bracketed!(previous in input);
+        let mut previous_tables = vec![];
+        while !previous.is_empty() {
+            let table;
+            syn::braced!(table in previous);
+            previous_tables.push(table.parse()?);
+        }
+        let joined;
+        syn::braced!(joined in input);
+        Ok(JoinedInput {
+            table: table.parse()?,
+            previous: previous_tables,
+            joined: joined.parse()?,
+        })
+    }
+}
+
+pub fn expand(input: JoinedInput) -> Result<TokenStream> {
+    let JoinedInput {
+        table,
+        mut previous,
+        joined,
+    } = input;
+    previous.push(table);
+
+    // resolve the next table, if there is one left
+    let (_, joins) = parse::table_paths(&joined)?;
+    if let Some(next) = joins.get(previous.len() - 1) {
+        return Ok(callback::with_table(
+            &next.table,
+            quote!(ormx::__joined),
+            quote!([ #( { #previous } )* ] { #joined }),
+        ));
+    }
+
+    // all tables have to use the backend of the first one
+    let backend = BackendKind::select(&previous[0])?;
+    for table in &previous[1..] {
+        if BackendKind::select(table)? != backend {
+            return Err(Error::new(
+                joined.ident.span(),
+                format!(
+                    "`{}` and `{}` use different backends",
+                    previous[0].ident, table.ident
+                ),
+            ));
+        }
+    }
+    dispatch!(backend => expand_with(previous, joined))
+}
+
+fn expand_with<B: Backend>(tables: Vec<DeriveInput>, joined: DeriveInput) -> Result<TokenStream> {
+    let tables = tables
+        .iter()
+        .map(Table::<B>::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let parsed = Joined::new(tables, &joined)?;
+    Ok(B::impl_joined(&parsed))
+}
@@ -0,0 +1,152 @@
+use syn::{ext::IdentExt, parse::ParseStream, DeriveInput, Error, Ident, Path, Result, Token};
+
+use super::Joined;
+use crate::{
+    attrs::{parse_attrs, Join, JoinedAttr, JoinedFieldAttr},
+    backend::Backend,
+    table::Table,
+    utils::{collect_all, missing_attr, named_fields, set_once},
+};
+
+/// Parses `#[ormx(from = ..)]` and all `#[ormx(join(..))]` of the joined struct.
+pub fn table_paths(input: &DeriveInput) -> Result<(Path, Vec<Join>)> {
+    let mut from = None;
+    let mut joins = vec![];
+    for (span, attr) in parse_attrs::<JoinedAttr>(&input.attrs)? {
+        match attr {
+            JoinedAttr::From(x) => set_once(&mut from, x, span)?,
+            JoinedAttr::Join(x) => joins.push(x),
+            JoinedAttr::OrderBy(_) => (),
+        }
+    }
+    let from = from.ok_or_else(|| missing_attr("from", input.ident.span()))?;
+    if joins.is_empty() {
+        return Err(missing_attr("join", input.ident.span()));
+    }
+    Ok((from, joins))
+}
+
+impl<B: Backend> Joined<B> {
+    pub fn new(tables: Vec<Table<B>>, input: &DeriveInput) -> Result<Self> {
+        let (from_path, joins) = table_paths(input)?;
+        if let Some(param) = input.generics.params.first() {
+            return Err(Error::new_spanned(
+                param,
+                "derive(Joined) does not support generics",
+            ));
+        }
+
+        let mut order_by = None;
+        for (span, attr) in parse_attrs::<JoinedAttr>(&input.attrs)? {
+            if let JoinedAttr::OrderBy(x) = attr {
+                set_once(&mut order_by, x, span)?;
+            }
+        }
+
+        let conditions = collect_all(
+            joins
+                .iter()
+                .zip(&tables[1..])
+                .map(|(join, table)| condition(join, &tables[0], table)),
+        )?;
+
+        let mut fields = vec![];
+        let mut field_tables = vec![];
+        for field in named_fields(input)? {
+            let ident = field.ident.clone().unwrap();
+
+            let mut table_path = None;
+            let mut source = None;
+            for (span, attr) in parse_attrs::<JoinedFieldAttr>(&field.attrs)? {
+                match attr {
+                    JoinedFieldAttr::Table(x) => set_once(&mut table_path, x, span)?,
+                    JoinedFieldAttr::Field(x) => set_once(&mut source, x, span)?,
+                }
+            }
+            let source = source.unwrap_or_else(|| ident.clone());
+
+            let mut matches = tables
+                .iter()
+                .filter(|table| match &table_path {
+                    Some(path) => path.segments.last().unwrap().ident == table.ident,
+                    None => true,
+                })
+                .filter_map(|table| {
+                    let column = table.fields.iter().find(|f| f.field == source)?;
+                    Some((table, column))
+                })
+                .collect::<Vec<_>>();
+
+            let (table, column) = match matches.len() {
+                1 => matches.remove(0),
+                0 => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!("none of the joined tables has a field named `{source}`"),
+                    ))
+                }
+                _ => {
+                    let names = matches.iter().map(|(t, _)| format!("`{}`", t.ident));
+                    return Err(Error::new(
+                        ident.span(),
+                        format!(
+                            "`{source}` is a field of {}, use #[ormx(table = ..)] to select one",
+                            names.collect::<Vec<_>>().join(" and ")
+                        ),
+                    ));
+                }
+            };
+
+            let mut column = column.clone();
+            column.field = ident;
+            column.ty = field.ty.clone();
+            fields.push(column);
+            field_tables.push(table.name());
+        }
+
+        Ok(Joined {
+            ident: input.ident.clone(),
+            vis: input.vis.clone(),
+            from_path,
+            tables,
+            conditions,
+            fields,
+            field_tables,
+            order_by,
+        })
+    }
+}
+
+/// Parses `on = "left = right"`, where `left` is a field of the first table and `right` a field of
+/// the joined table, into a condition on their columns.
+fn condition<B: Backend>(join: &Join, from: &Table<B>, table: &Table<B>) -> Result<String> {
+    let (left, right) = join
+        .on
+        .parse_with(|input: ParseStream| {
+            let left = Ident::parse_any(input)?;
+            input.parse::<Token![=]>()?;
+            let right = Ident::parse_any(input)?;
+            Ok((left, right))
+        })
+        .map_err(|err| Error::new(err.span(), "expected a condition like \"team_id = id\""))?;
+
+    let column = |table: &Table<B>, field: Ident| -> Result<String> {
+        let field = field.unraw();
+        let column = table
+            .fields
+            .iter()
+            .find(|f| f.field.unraw() == field)
+            .ok_or_else(|| {
+                Error::new(
+                    join.on.span(),
+                    format!("`{}` has no field named `{field}`", table.ident),
+                )
+            })?;
+        Ok(format!("{}.{}", table.name(), column.column()))
+    };
+    Ok(format!(
+        "{} = {}",
+        column(from, left)?,
+        column(table, right)?
+    ))
+}
@@ -7,9 +7,13 @@
 
 mod attrs;
 mod backend;
+mod joined;
 mod patch;
+mod projection;
+mod queue;
 mod table;
 mod utils;
+mod view;
 
 /// Derives [Table](trait.Table.html) and generates a struct for inserting rows and accessors
 /// to certain fields.
@@ -37,6 +41,15 @@ mod utils;
 /// This is a central requirement of ormx, and if your table does not fulfill this requirement, ormx
 /// is not what you are looking for.
 ///
+/// # Schemas
+/// Tables outside of the default schema can be referred to by using `#[ormx(schema = "auth")]`
+/// or by qualifying the table name: `#[ormx(table = "auth.users")]`.
+/// The schema, table and column names are quoted separately in every generated query.
+///
+/// Unqualified tables are resolved using the `search_path` of the connection. With PostgreSQL,
+/// this can be used to select a schema at runtime (e.g for a schema per tenant), see
+/// [begin_in_schema](fn.begin_in_schema.html).
+///
 /// # CRUD
 /// See the documentation of [Table](trait.Table.html)
 ///
@@ -48,11 +61,27 @@ mod utils;
 ///
 /// If you're using an auto-incrementing integer as key, it needs to be marked with `#[ormx(default)]` as well.
 ///
+/// Since MySQL does not support `RETURNING`, a generated key which is not an auto-incrementing
+/// integer (e.g a UUID stored as `BINARY(16)`) needs one of these on the ID field:
+/// - `#[ormx(default, generate = "UUID_TO_BIN(UUID())")]`, evaluating the expression before
+///   inserting the row with the resulting ID
+/// - `#[ormx(default, lookup = email)]`, looking up the ID of the inserted row by the value of a
+///   unique field, e.g when it's generated by a trigger
+///
+/// Without either of them, a defaulted ID which is not an integer is a compile error.
+///
 /// By default, this struct will be named `Insert{struct_name}`, though this can be changed by
 /// supplying a custom name: `#[ormx(insertable = CreateUser)]`.
 /// The generated struct can be used by [Table::insert](trait.Table.html) or
 /// [Insert::insert](trait.Insert.html).
 ///
+/// With PostgreSQL, the struct also gets
+/// `{pub} async fn copy_in(db, rows: impl IntoIterator<Item = Self>) -> Result<u64>`, which loads
+/// many rows at once using `COPY .. FROM STDIN (FORMAT binary)`. Since the binary format is not
+/// converted by the database, the type of each field has to match its column exactly.
+/// The types of the columns are looked up before copying, so arrays and composite types of
+/// `custom_type` fields are supported as well.
+///
 /// # Deletable
 /// ormx will implement [Delete](trait.Delete.html) for your struct when using
 /// `#[ormx(deletable)].
@@ -72,6 +101,10 @@ mod utils;
 /// **`#[ormx(get_many)]`**:  
 /// `{pub} async fn get_by_{field_name}(&{field_type}) -> Result<Vec<Self>>`
 ///
+/// Additionally, `#[ormx(get_many)]` generates a streaming variant, named like the getter with
+/// `stream_` in place of the `get_` prefix:
+/// `{pub} fn stream_by_{field_name}(&{field_type}) -> impl Stream<Item = Result<Self>>`
+///
 /// By default, the function will be named `get_by_{field_name)`, though this can be changed by
 /// supplying a custom name: `#[ormx(get_one = by_id)]`.
 /// By default, the function will take a reference to the type of the annotated field as an argument,
@@ -88,12 +121,121 @@ mod utils;
 /// By default, the function will be named `set_{field_name)`, though this can be changed by
 /// supplying a custom name: `#[ormx(set = set_name)]`.
 ///
+/// # Accessors: Counters
+/// For fields annotated with `#[ormx(counter)]`, ormx generates
+/// `{pub} async fn increment_{field_name}(&mut self, db, delta: {field_type}) -> Result<()>` and
+/// `{pub} async fn decrement_{field_name}(&mut self, db, delta: {field_type}) -> Result<()>`.
+/// Unlike a setter, these compute the new value in the database, e.g
+/// `UPDATE users SET logins = logins + $1 WHERE id = $2 RETURNING logins`, so concurrent updates
+/// are not lost. The field is then set to the value returned by the database.
+/// Since MySQL and MariaDB do not support `UPDATE .. RETURNING`, the value is selected afterwards
+/// within the same transaction.
+///
+/// # Columns
+/// ormx generates a `{struct_name}Column` enum with a variant for every field, e.g
+/// `UserColumn::LastLogin`. `as_sql()` returns the quoted column, so renaming a column using
+/// `#[ormx(column = ..)]` is picked up by every query built from it.
+///
+/// # Sorting
+/// Fields annotated with `#[ormx(sortable)]` become variants of a generated `{struct_name}Sort`
+/// enum, e.g `UserSort::EmailAsc` and `UserSort::EmailDesc`.
+/// The order can then be chosen at runtime using these generated functions:
+///
+/// `{pub} fn stream_all_sorted(db, sort: UserSort, offset: i64, limit: i64) -> impl Stream<Item = Result<Self>>`
+///
+/// `{pub} async fn all_sorted(db, sort: UserSort, offset: i64, limit: i64) -> Result<Vec<Self>>`
+///
+/// Every variant maps to its own query, which is checked at compile time.
+/// Rows with equal values are ordered by their ID.
+/// The column of a variant is returned by `UserSort::column()`.
+///
+/// # Batches
+/// `{pub} async fn for_each_batch(pool, batch_size: i64, f) -> Result<(), E>` calls `f` with all
+/// rows of the table in batches of at most `batch_size` rows, ordered by their ID.
+/// Each batch is loaded by its own short query, continuing after the last ID of the previous
+/// batch, so even huge tables can be processed without keeping a query or transaction open.
+/// `f` returns a future resolving to `Result<(), E>`, where `E: From<sqlx::Error>`.
+///
+/// # DDL
+/// When using `#[ormx(ddl)]`, ormx generates `{pub} fn create_table_sql(dialect: Dialect) -> String`,
+/// returning a `CREATE TABLE` statement for the given dialect.
+/// The SQL type of a column is inferred from the type of its field. For custom types, or to
+/// override the inferred type, use `#[ormx(sql_type = "VARCHAR(64)")]`.
+/// Additionally, columns can be annotated with
+/// - `#[ormx(sql_default = "now()")]`, the default value of the column
+/// - `#[ormx(unique)]`, adding a `UNIQUE` constraint
+/// - `#[ormx(index)]`, creating an index on the column
+/// - `#[ormx(references = "teams(id)")]`, adding a foreign key constraint
+///
+/// An integer ID using `#[ormx(default)]` becomes `SERIAL`/`AUTO_INCREMENT`. Every other
+/// `#[ormx(default)]` column which is not nullable requires a `sql_default`.
+///
+/// # Metadata
+/// ormx implements [TableMeta](trait.TableMeta.html) for every table, exposing the table name,
+/// the ID column, all columns and the generated queries as associated constants, e.g
+/// `User::TABLE_NAME` or `User::GET_SQL`.
+///
+/// # Schema verification
+/// Using [verify_schema](fn.verify_schema.html) or [verify_all](fn.verify_all.html), the database
+/// can be checked at runtime for missing tables or columns, nullability mismatches and
+/// incompatible column types. Generic tables are not included in `verify_all`.
+///
+/// # Generics
+/// Generic parameters and where-clauses of the struct are forwarded to all generated impls and
+/// to the insert struct. Every type parameter therefore has to be used by a field of the struct.
+///
+/// Fields which are not stored in the database, e.g a marker `PhantomData<T>`, are annotated with
+/// `#[ormx(skip)]`. Their type has to implement `Default`, which is used when reading a row.
+/// Skipped fields are kept in the insert struct, but are not inserted, so a type parameter only
+/// used by a skipped field is still used by the insert struct.
+///
+/// # Backends
+/// Multiple backends can be enabled at once, e.g `postgres` and `mysql`. In that case, every
+/// table has to select its backend using `#[ormx(backend = postgres)]` (or `mysql`, `mariadb`).
+/// Patches and views select their backend the same way, while projections and joined structs use
+/// the backend of their tables.
+/// Generic code can refer to the database of a table as `<T as Table>::Database`, since
+/// `ormx::Db` is only available if a single backend is enabled.
+///
+/// # Caching
+/// With `#[ormx(cache)]`, `Table::get` and the `get_one` getters first look up the row in the
+/// cache of the table, see [Cached](trait.Cached.html). The generated `update`, setters,
+/// counters, patches and `delete_row` invalidate the cached row.
+/// Only rows read using a pool are cached, and rows are not cached again for a short while after
+/// being written, since the transaction writing them may not be committed yet.
+/// Cached tables must implement `Clone`, must not be generic, and the fields of cached getters
+/// must implement `Hash` and `Eq`.
+///
+/// # Row locks
+/// With `#[ormx(lock)]`, ormx generates variants of `get` and of every getter which lock the
+/// selected rows until the end of the transaction, e.g `get_for_update(db, id)` or
+/// `by_email_for_share(db, email)`. Each of them is also available with the suffix `_nowait`,
+/// failing if a row is already locked, and `_skip_locked`, ignoring locked rows. The
+/// `_skip_locked` variants of `get` and `get_one` getters return an `Option`.
+/// `get_many` getters lock their rows ordered by the ID, so two transactions locking overlapping
+/// rows can not deadlock each other.
+///
+/// # Change feeds
+/// With `#[ormx(notify)]` (PostgreSQL only), ormx generates `notify_trigger_sql()`, returning the
+/// SQL for a trigger which publishes every insert, update and delete on the channel
+/// `{table}_changes` (configurable using `#[ormx(notify = "channel")]`).
+/// `User::changes(listener)` then streams these as [Change](enum.Change.html)s, using the given
+/// `sqlx::postgres::PgListener` to listen on the channel of the table and to query inserted and
+/// updated rows. `User::changes_from_pool(&pool)` connects its own listener instead.
+/// The ID type has to implement `FromStr`.
+///
+/// # Unchecked queries
+/// With `#[ormx(unchecked)]` or the `unchecked` feature, ormx generates the same API, but uses
+/// queries which are not checked at compile time, so no `DATABASE_URL` is required for building.
+/// Rows are decoded by column name using a generated `sqlx::FromRow` implementation, so the struct
+/// must not derive `FromRow` itself. Projections and joined structs are unchecked if their (first)
+/// table is.
+///
 /// # Custom types
 /// When using custom types (which implement `sqlx::Type`), the field has to annotated with
 /// `#[ormx(custom_type)]`.
 /// This will use a column type override for querying this field
 /// (see [the sqlx docs on this](https://docs.rs/sqlx/0.4.0-beta.1/sqlx/macro.query_as.html#column-type-override-infer-from-struct-field)).
-#[proc_macro_error::proc_macro_error]
 #[proc_macro_derive(Table, attributes(ormx))]
 pub fn derive_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
@@ -104,8 +246,61 @@ pub fn derive_table(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     .into()
 }
 
+/// Derives a work queue on top of a [Table](trait.Table.html) (PostgreSQL only).
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(ormx::Table, ormx::Queue)]
+/// #[ormx(table = "jobs", id = id, insertable, queue(status = state, run_at = run_at))]
+/// struct Job {
+///     #[ormx(default)]
+///     id: i64,
+///     payload: String,
+///     state: String,
+///     run_at: DateTime<Utc>,
+///     attempts: i32,
+/// }
+/// ```
+///
+/// The status column holds either `'pending'`, `'running'`, `'done'` or `'failed'`, e.g as text
+/// or as an enum. Jobs are enqueued by inserting them as `'pending'`, with `run_at` set to the
+/// time at which they become due. The attempts are counted in the field `attempts`, which can be
+/// changed using `queue(attempts = ..)`.
+///
+/// ormx generates these functions:
+///
+/// `{pub} async fn dequeue(db, n: i64) -> Result<Vec<Self>>` claims up to `n` due jobs using
+/// `FOR UPDATE SKIP LOCKED`, so concurrent workers never claim the same job. Claimed jobs are
+/// marked as `'running'`, and `run_at` is set to the time they were claimed.
+///
+/// `{pub} async fn complete(&self, db) -> Result<bool>` marks a claimed job as `'done'`.
+///
+/// `{pub} async fn fail(&self, db, retry: &Retry) -> Result<bool>` reschedules a claimed job
+/// after the backoff of the [Retry](struct.Retry.html) configuration, or marks it as `'failed'`
+/// once its attempts are exhausted.
+///
+/// `{pub} async fn requeue_stale(db, timeout: Duration, retry: &Retry) -> Result<u64>` returns
+/// jobs which have been running for longer than `timeout` to the queue, e.g because their worker
+/// crashed. Like with `fail`, jobs which have used up their attempts are marked as `'failed'`.
+///
+/// `complete` and `fail` return `false` if the job has been requeued since it was claimed.
+#[proc_macro_derive(Queue, attributes(ormx))]
+pub fn derive_queue(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match queue::derive(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
 /// Derives [Patch](trait.Patch.html).
-#[proc_macro_error::proc_macro_error]
+///
+/// Like with `derive(Table)`, the table name can be schema-qualified using either
+/// `#[ormx(schema = "auth")]` or `#[ormx(table_name = "auth.users")]`.
+/// If multiple backends are enabled, the backend has to be selected using
+/// `#[ormx(backend = postgres)]`. `#[ormx(unchecked)]` generates a query which is not checked at
+/// compile time.
 #[proc_macro_derive(Patch, attributes(ormx))]
 pub fn derive_patch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
@@ -115,3 +310,129 @@ pub fn derive_patch(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     }
     .into()
 }
+
+/// Derives accessors for a struct containing a subset of the columns of a table.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(ormx::Projection)]
+/// #[ormx(table = User)]
+/// struct UserSummary {
+///     user_id: i32,
+///     email: String,
+/// }
+/// ```
+///
+/// Every field of the projection has to be a field of the table. Column names, custom types
+/// and the order of the table are taken from `derive(Table)`, so the fields can not be annotated.
+///
+/// The projection gets its own `get`, `stream_all`, `stream_all_paginated`, `all` and
+/// `all_paginated`, as well as all getters of the table, e.g `by_email`. These only select
+/// the columns of the projection.
+///
+/// The table has to be defined in the same crate.
+#[proc_macro_derive(Projection, attributes(ormx))]
+pub fn derive_projection(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match projection::derive(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+/// Derives read-only accessors for a database view.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(ormx::View)]
+/// #[ormx(view = "active_users", order_by = "last_login DESC")]
+/// struct ActiveUser {
+///     #[ormx(column = "id", get_optional)]
+///     user_id: i32,
+///     email: String,
+///     last_login: NaiveDateTime,
+/// }
+/// ```
+///
+/// Unlike `derive(Table)`, views do not need an ID and can not be inserted, updated or deleted.
+/// The view gets `stream_all`, `stream_all_paginated`, `all`, `all_paginated` and `count`.
+/// Without `#[ormx(order_by = ..)]`, the rows are returned in an unspecified order.
+///
+/// Like with `derive(Table)`, the view can be schema-qualified, can use `#[ormx(unchecked)]`,
+/// and fields can be annotated with `#[ormx(column = ..)]`, `#[ormx(custom_type)]` and the
+/// getter attributes.
+///
+/// # Materialized views (PostgreSQL)
+/// When using `#[ormx(materialized)]`, ormx additionally generates
+/// `{pub} async fn refresh(db, concurrently: bool) -> Result<()>`.
+#[proc_macro_derive(View, attributes(ormx))]
+pub fn derive_view(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match view::derive(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+/// Derives accessors for a struct containing fields of multiple joined tables.
+///
+/// # Example
+/// ```rust,ignore
+/// #[derive(ormx::Joined)]
+/// #[ormx(from = User, join(Team, on = "team_id = id"))]
+/// struct UserWithTeam {
+///     #[ormx(table = User, field = id)]
+///     user_id: i32,
+///     email: String,
+///     #[ormx(table = Team, field = name)]
+///     team_name: String,
+/// }
+/// ```
+///
+/// The tables are joined using `INNER JOIN`. The condition of a join compares a field of the
+/// `from` table on the left to a field of the joined table on the right, which are mapped to their
+/// columns like every other field.
+///
+/// Every field is looked up in the tables by its name. If multiple tables contain a field with
+/// that name, the table has to be selected using `#[ormx(table = ..)]`. A field of the table can
+/// be selected under a different name using `#[ormx(field = ..)]`. Column names and custom types
+/// are taken from `derive(Table)`.
+///
+/// The struct gets `get` (by the ID of the `from` table), `stream_all`, `stream_all_paginated`,
+/// `all`, `all_paginated` and the getters of its fields. By default, rows are ordered by the ID
+/// of the `from` table in descending order, which can be changed using `#[ormx(order_by = ..)]`.
+///
+/// All tables have to be defined in the same crate.
+#[proc_macro_derive(Joined, attributes(ormx))]
+pub fn derive_joined(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    match joined::derive(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __joined(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as joined::JoinedInput);
+    match joined::expand(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __projection(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as projection::ProjectionInput);
+    match projection::expand(input) {
+        Ok(ok) => ok,
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
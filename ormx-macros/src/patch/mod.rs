@@ -2,18 +2,21 @@ use std::convert::TryFrom;
 
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{DeriveInput, Ident, Path, Result, Type};
+use syn::{DeriveInput, Generics, Ident, Path, Result, Type};
 
-use crate::backend::{Backend, Implementation};
+use crate::backend::{dispatch, Backend, BackendKind};
 
 mod parse;
 
 pub struct Patch {
     pub ident: Ident,
+    pub generics: Generics,
+    pub schema: Option<String>,
     pub table_name: String,
     pub table: Path,
     pub id: String,
     pub fields: Vec<PatchField>,
+    pub unchecked: bool,
 }
 
 pub struct PatchField {
@@ -24,12 +27,29 @@ pub struct PatchField {
     pub by_ref: bool,
 }
 
+impl Patch {
+    pub fn name<B: Backend>(&self) -> String {
+        B::quote_table(self.schema.as_deref(), &self.table_name)
+    }
+
+    pub fn id_column<B: Backend>(&self) -> String {
+        B::quote(&self.id)
+    }
+}
+
 impl PatchField {
-    pub fn fmt_as_argument(&self) -> TokenStream {
+    pub fn column<B: Backend>(&self) -> String {
+        B::quote(&self.column)
+    }
+
+    pub fn fmt_as_argument(&self, unchecked: bool) -> TokenStream {
         let ident = &self.ident;
         let ty = &self.ty;
+        if unchecked {
+            return quote!(&self.#ident);
+        }
 
-        let mut out = quote!(#ident);
+        let mut out = quote!(self.#ident);
         if self.custom_type {
             out = quote!(#out as #ty);
         }
@@ -42,5 +62,9 @@ impl PatchField {
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
     let parsed = Patch::try_from(&input)?;
-    Ok(Implementation::impl_patch(&parsed))
+    Ok(dispatch!(BackendKind::select(&input)? => impl_patch(&parsed)))
+}
+
+fn impl_patch<B: Backend>(patch: &Patch) -> TokenStream {
+    B::impl_patch(patch)
 }
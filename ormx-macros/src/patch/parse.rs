@@ -1,46 +1,50 @@
 use std::convert::TryFrom;
 
-use syn::{Data, DeriveInput, Error, Field, Result};
+use syn::{DeriveInput, Error, Field, Result};
 
 use super::Patch;
 use crate::{
     attrs::{parse_attrs, PatchAttr, PatchFieldAttr},
     patch::PatchField,
-    utils::{missing_attr, set_once},
+    utils::{collect_all, missing_attr, named_fields, resolve_table_name, set_once},
 };
 
 impl TryFrom<&DeriveInput> for Patch {
     type Error = Error;
 
     fn try_from(value: &DeriveInput) -> Result<Self> {
-        let data = match &value.data {
-            Data::Struct(s) => s,
-            _ => panic!("not a struct with named fields"),
-        };
-
-        let fields = data
-            .fields
-            .iter()
-            .map(PatchField::try_from)
-            .collect::<Result<Vec<_>>>()?;
+        let fields = collect_all(named_fields(value)?.iter().map(PatchField::try_from))?;
 
         let mut table = None;
         let mut table_name = None;
+        let mut schema = None;
         let mut id = None;
-        for attr in parse_attrs::<PatchAttr>(&value.attrs)? {
+        let mut unchecked = None;
+        for (span, attr) in parse_attrs::<PatchAttr>(&value.attrs)? {
             match attr {
-                PatchAttr::Table(x) => set_once(&mut table, x)?,
-                PatchAttr::TableName(x) => set_once(&mut table_name, x)?,
-                PatchAttr::Id(x) => set_once(&mut id, x)?,
+                PatchAttr::Table(x) => set_once(&mut table, x, span)?,
+                PatchAttr::TableName(x) => set_once(&mut table_name, x, span)?,
+                PatchAttr::Schema(x) => set_once(&mut schema, x, span)?,
+                PatchAttr::Id(x) => set_once(&mut id, x, span)?,
+                PatchAttr::Unchecked(_) => set_once(&mut unchecked, true, span)?,
+                // selected by `BackendKind::select`
+                PatchAttr::Backend(_) => (),
             }
         }
 
+        let span = value.ident.span();
+        let table_name = table_name.ok_or_else(|| missing_attr("table_name", span))?;
+        let (schema, table_name) = resolve_table_name(schema, table_name)?;
+
         Ok(Patch {
             ident: value.ident.clone(),
-            table_name: table_name.ok_or_else(|| missing_attr("table_name"))?,
-            table: table.ok_or_else(|| missing_attr("table"))?,
-            id: id.ok_or_else(|| missing_attr("id"))?,
+            generics: value.generics.clone(),
+            schema,
+            table_name,
+            table: table.ok_or_else(|| missing_attr("table", span))?,
+            id: id.ok_or_else(|| missing_attr("id", span))?,
             fields,
+            unchecked: unchecked.unwrap_or(cfg!(feature = "unchecked")),
         })
     }
 }
@@ -54,11 +58,11 @@ impl TryFrom<&Field> for PatchField {
         let mut column = None;
         let mut custom_type = None;
         let mut by_ref = None;
-        for attr in parse_attrs::<PatchFieldAttr>(&value.attrs)? {
+        for (span, attr) in parse_attrs::<PatchFieldAttr>(&value.attrs)? {
             match attr {
-                PatchFieldAttr::Column(x) => set_once(&mut column, x)?,
-                PatchFieldAttr::CustomType(_) => set_once(&mut custom_type, true)?,
-                PatchFieldAttr::ByRef(_) => set_once(&mut by_ref, true)?,
+                PatchFieldAttr::Column(x) => set_once(&mut column, x, span)?,
+                PatchFieldAttr::CustomType(_) => set_once(&mut custom_type, true, span)?,
+                PatchFieldAttr::ByRef(_) => set_once(&mut by_ref, true, span)?,
             }
         }
 
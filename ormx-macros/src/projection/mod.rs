@@ -0,0 +1,75 @@
+use std::convert::TryFrom;
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    DeriveInput, Ident, Path, Result, Visibility,
+};
+
+use crate::{
+    backend::{dispatch, Backend, BackendKind},
+    table::{callback, Table, TableField},
+};
+
+mod parse;
+
+pub struct Projection<B: Backend> {
+    pub ident: Ident,
+    pub vis: Visibility,
+    /// Path of the table, as given by `#[ormx(table = ..)]`
+    pub table_path: Path,
+    /// The table this projection selects from
+    pub table: Table<B>,
+    /// The selected fields of the table, with the types of the projection
+    pub fields: Vec<TableField<B>>,
+}
+
+impl<B: Backend> Projection<B> {
+    pub fn select_column_list(&self) -> String {
+        self.fields
+            .iter()
+            .map(|field| field.fmt_for_select(self.table.unchecked))
+            .join(", ")
+    }
+}
+
+/// Entry point of `derive(Projection)`, which invokes the macro generated by `derive(Table)`.
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let table = parse::table_path(&input)?;
+    Ok(callback::with_table(
+        &table,
+        quote!(ormx::__projection),
+        quote!({ #input }),
+    ))
+}
+
+/// Input of `ormx::__projection!`: the definitions of the table and the projection.
+pub struct ProjectionInput {
+    table: DeriveInput,
+    projection: DeriveInput,
+}
+
+impl Parse for ProjectionInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let table;
+        syn::braced!(table in input);
+        let projection;
+        syn::braced!(projection in input);
+        Ok(ProjectionInput {
+            table: table.parse()?,
+            projection: projection.parse()?,
+        })
+    }
+}
+
+pub fn expand(input: ProjectionInput) -> Result<TokenStream> {
+    dispatch!(BackendKind::select(&input.table)? => expand_with(input))
+}
+
+fn expand_with<B: Backend>(input: ProjectionInput) -> Result<TokenStream> {
+    let table = Table::<B>::try_from(&input.table)?;
+    let parsed = Projection::new(table, &input.projection)?;
+    Ok(B::impl_projection(&parsed))
+}
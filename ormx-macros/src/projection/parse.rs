@@ -0,0 +1,60 @@
+use syn::{DeriveInput, Error, Path, Result};
+
+use super::Projection;
+use crate::{
+    attrs::{parse_attrs, ProjectionAttr},
+    backend::Backend,
+    table::Table,
+    utils::{collect_all, missing_attr, named_fields, set_once},
+};
+
+/// Parses `#[ormx(table = ..)]` of the projection.
+pub fn table_path(input: &DeriveInput) -> Result<Path> {
+    let mut table = None;
+    for (span, attr) in parse_attrs::<ProjectionAttr>(&input.attrs)? {
+        match attr {
+            ProjectionAttr::Table(x) => set_once(&mut table, x, span)?,
+        }
+    }
+    table.ok_or_else(|| missing_attr("table", input.ident.span()))
+}
+
+impl<B: Backend> Projection<B> {
+    pub fn new(table: Table<B>, input: &DeriveInput) -> Result<Self> {
+        let table_path = table_path(input)?;
+        if let Some(param) = input.generics.params.first() {
+            return Err(Error::new_spanned(
+                param,
+                "derive(Projection) does not support generics",
+            ));
+        }
+
+        let fields = collect_all(named_fields(input)?.iter().map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            if let Some(attr) = field.attrs.iter().find(|a| a.path().is_ident("ormx")) {
+                return Err(Error::new_spanned(
+                    attr,
+                    "fields of a projection can not be annotated, the columns are taken from the table",
+                ));
+            }
+
+            let column = table.fields.iter().find(|column| column.field == *ident);
+            let mut column = column.cloned().ok_or_else(|| {
+                Error::new(
+                    ident.span(),
+                    format!("`{}` has no field named `{}`", table.ident, ident),
+                )
+            })?;
+            column.ty = field.ty.clone();
+            Ok(column)
+        }))?;
+
+        Ok(Projection {
+            ident: input.ident.clone(),
+            vis: input.vis.clone(),
+            table_path,
+            table,
+            fields,
+        })
+    }
+}
@@ -0,0 +1,22 @@
+use std::convert::TryFrom;
+
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Result};
+
+use crate::{
+    backend::{dispatch, Backend, BackendKind},
+    table::Table,
+    utils::missing_attr,
+};
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    dispatch!(BackendKind::select(&input)? => derive_with(input))
+}
+
+fn derive_with<B: Backend>(input: DeriveInput) -> Result<TokenStream> {
+    let parsed = Table::<B>::try_from(&input)?;
+    if parsed.queue.is_none() {
+        return Err(missing_attr("queue(..)", input.ident.span()));
+    }
+    Ok(B::impl_queue(&parsed))
+}
@@ -0,0 +1,46 @@
+//! Passing the definition of a table on to other macros.
+//!
+//! A derive macro can not look at the definition of another struct. Therefore, `derive(Table)`
+//! generates a `macro_rules!` macro next to the table, which invokes the given macro with the
+//! definition of the table prepended to its input:
+//! `__ormx_table_User! { ormx::__projection { .. } }` expands to
+//! `ormx::__projection! { { <definition of User> } .. }`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Ident, Path, PathArguments};
+
+/// Returns the ident of the macro generated by `derive(Table)` for the given table.
+fn callback_ident(table: &Ident) -> Ident {
+    Ident::new(&format!("__ormx_table_{}", table), table.span())
+}
+
+/// Generates the callback macro for a table.
+pub fn table_callback(input: &DeriveInput) -> TokenStream {
+    let callback = callback_ident(&input.ident);
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(unused_macros)]
+        macro_rules! #callback {
+            ($($macro:ident)::+ { $($input:tt)* }) => {
+                $($macro)::+! { { #input } $($input)* }
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        pub(crate) use #callback;
+    }
+}
+
+/// Invokes `target` (e.g `ormx::__projection`) with the definition of `table` prepended to
+/// `input`.
+pub fn with_table(table: &Path, target: TokenStream, input: TokenStream) -> TokenStream {
+    let mut path = table.clone();
+    let last = path.segments.last_mut().unwrap();
+    last.ident = callback_ident(&last.ident);
+    last.arguments = PathArguments::None;
+
+    quote!(#path! { #target { #input } })
+}
@@ -3,24 +3,39 @@ use std::{convert::TryFrom, marker::PhantomData};
 use itertools::Itertools;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{quote, ToTokens};
-use syn::{Attribute, DeriveInput, Result, Type, Visibility};
+use syn::{ext::IdentExt, Attribute, DeriveInput, Generics, LitStr, Result, Type, Visibility};
 
 use crate::{
-    attrs::{Getter, Insertable},
-    backend::{Backend, Implementation},
+    attrs::{Getter, Insertable, Queue},
+    backend::{dispatch, Backend, BackendKind, Rows},
 };
 
+pub mod callback;
 mod parse;
 
 pub struct Table<B: Backend> {
     pub ident: Ident,
     pub vis: Visibility,
+    pub generics: Generics,
+    schema: Option<String>,
     table: String,
     pub id: TableField<B>,
     pub fields: Vec<TableField<B>>,
+    /// Fields using `#[ormx(skip)]`, which are not columns
+    pub skipped: Vec<TableField<B>>,
     pub insertable: Option<Insertable>,
     pub deletable: bool,
     pub order_by: Option<String>,
+    pub ddl: bool,
+    /// Generate queries which are not checked at compile time
+    pub unchecked: bool,
+    pub cache: bool,
+    /// Generate locking variants of `get` and the getters
+    pub lock: bool,
+    /// Channel on which changes are published using `#[ormx(notify)]`
+    pub notify: Option<String>,
+    /// Columns used by `derive(Queue)`
+    pub queue: Option<Queue>,
 }
 
 #[derive(Clone)]
@@ -34,7 +49,22 @@ pub struct TableField<B: Backend> {
     pub get_optional: Option<Getter>,
     pub get_many: Option<Getter>,
     pub set: Option<Ident>,
+    /// Span of `#[ormx(counter)]`, generating `increment_{field}` and `decrement_{field}`
+    pub counter: Option<Span>,
     pub by_ref: bool,
+    /// Span of `#[ormx(sortable)]`
+    pub sortable: Option<Span>,
+    pub sql_type: Option<String>,
+    pub sql_default: Option<String>,
+    pub unique: bool,
+    pub index: bool,
+    pub references: Option<String>,
+    /// SQL expression evaluated before inserting to generate the ID (MySQL only)
+    pub generate: Option<LitStr>,
+    /// Field used to look up the generated ID after inserting (MySQL only)
+    pub lookup: Option<Ident>,
+    /// Not a column, set to `Default::default()` when reading a row
+    pub skip: bool,
     pub insert_attrs: Vec<Attribute>,
     pub _phantom: PhantomData<*const B>,
 }
@@ -53,40 +83,85 @@ impl<B: Backend> Table<B> {
         self.fields.iter().filter(|field| field.default)
     }
 
+    /// Describes how the rows of this table are decoded by `query_as`.
+    pub fn rows(&self) -> Rows {
+        Rows::skipping(
+            self.unchecked,
+            self.fields
+                .iter()
+                .map(|field| field.field.clone())
+                .collect(),
+            self.skipped
+                .iter()
+                .map(|field| field.field.clone())
+                .collect(),
+        )
+    }
+
     pub fn select_column_list(&self) -> String {
         self.fields
             .iter()
-            .map(|field| field.fmt_for_select())
+            .map(|field| field.fmt_for_select(self.unchecked))
             .join(", ")
     }
 
+    pub fn column_ident(&self) -> Ident {
+        Ident::new(&format!("{}Column", self.ident), Span::call_site())
+    }
+
+    pub fn sort_ident(&self) -> Ident {
+        Ident::new(&format!("{}Sort", self.ident), Span::call_site())
+    }
+
+    pub fn schema_name(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table
+    }
+
     pub fn name(&self) -> String {
-        let q = B::QUOTE;
-        format!("{q}{}{q}", self.table)
+        B::quote_table(self.schema.as_deref(), &self.table)
     }
 }
 
 impl<B: Backend> TableField<B> {
-    pub fn fmt_for_select(&self) -> String {
-        let q = B::QUOTE;
+    /// Formats the column for a `SELECT`. If the query is `unchecked`, the column is always
+    /// named like the field, since the row is decoded by name.
+    pub fn fmt_for_select(&self, unchecked: bool) -> String {
+        self.fmt_column_for_select(self.column(), unchecked)
+    }
 
-        if self.custom_type {
-            format!(
-                "{q}{}{q} AS {q}{}!: {}{q}",
-                self.column_name,
-                self.field,
-                self.ty.to_token_stream()
-            )
+    /// Like `fmt_for_select`, but qualifies the column with the (quoted) name of its table.
+    pub fn fmt_for_select_qualified(&self, table: &str, unchecked: bool) -> String {
+        self.fmt_column_for_select(format!("{}.{}", table, self.column()), unchecked)
+    }
+
+    fn fmt_column_for_select(&self, column: String, unchecked: bool) -> String {
+        if unchecked {
+            let field = self.field.unraw().to_string();
+            if field == self.column_name {
+                column
+            } else {
+                format!("{} AS {}", column, B::quote(&field))
+            }
+        } else if self.custom_type {
+            let alias = format!("{}!: {}", self.field, self.ty.to_token_stream());
+            format!("{} AS {}", column, B::quote(&alias))
         } else if self.field == self.column_name {
-            self.column()
+            column
         } else {
-            format!("{q}{}{q} AS {q}{}{q}", self.column_name, self.field)
+            format!("{} AS {}", column, B::quote(&self.field.to_string()))
         }
     }
 
-    pub fn fmt_as_argument(&self) -> TokenStream {
+    pub fn fmt_as_argument(&self, unchecked: bool) -> TokenStream {
         let ident = &self.field;
         let ty = &self.ty;
+        if unchecked {
+            return quote!(&self.#ident);
+        }
 
         let mut out = quote!(self.#ident);
         let mut ty = quote!(#ty);
@@ -102,8 +177,20 @@ impl<B: Backend> TableField<B> {
     }
 
     pub fn column(&self) -> String {
-        let q = B::QUOTE;
-        format!("{q}{}{q}", self.column_name)
+        B::quote(&self.column_name)
+    }
+
+    pub fn column_name(&self) -> &str {
+        &self.column_name
+    }
+
+    /// Returns true if any of the attributes only used by `#[ormx(ddl)]` is present
+    pub fn has_ddl_attrs(&self) -> bool {
+        self.sql_type.is_some()
+            || self.sql_default.is_some()
+            || self.unique
+            || self.index
+            || self.references.is_some()
     }
 }
 
@@ -122,14 +209,28 @@ impl Getter {
 }
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
-    let parsed = Table::try_from(&input)?;
+    dispatch!(BackendKind::select(&input)? => derive_with(input))
+}
 
-    let impl_table = Implementation::impl_table(&parsed);
-    let delete = Implementation::impl_delete(&parsed);
-    let insert_struct = Implementation::insert_struct(&parsed);
-    let impl_insert = Implementation::impl_insert(&parsed);
-    let getters = Implementation::impl_getters(&parsed);
-    let setters = Implementation::impl_setters(&parsed);
+fn derive_with<B: Backend>(input: DeriveInput) -> Result<TokenStream> {
+    let parsed = Table::<B>::try_from(&input)?;
+
+    let impl_table = B::impl_table(&parsed);
+    let delete = B::impl_delete(&parsed);
+    let insert_struct = B::insert_struct(&parsed);
+    let impl_insert = B::impl_insert(&parsed);
+    let getters = B::impl_getters(&parsed);
+    let setters = B::impl_setters(&parsed);
+    let counters = B::impl_counters(&parsed);
+    let column_enum = B::impl_column_enum(&parsed);
+    let sort = B::impl_sort(&parsed);
+    let batches = B::impl_batches(&parsed);
+    let ddl = B::impl_ddl(&parsed);
+    let table_meta = B::impl_table_meta(&parsed);
+    let cache = B::impl_cache(&parsed);
+    let locks = B::impl_locks(&parsed);
+    let notify = B::impl_notify(&parsed);
+    let projection_callback = callback::table_callback(&input);
 
     Ok(quote! {
         #impl_table
@@ -138,5 +239,15 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream> {
         #impl_insert
         #getters
         #setters
+        #counters
+        #column_enum
+        #sort
+        #batches
+        #ddl
+        #table_meta
+        #cache
+        #locks
+        #notify
+        #projection_callback
     })
 }
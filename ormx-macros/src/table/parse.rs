@@ -1,13 +1,15 @@
-use std::{convert::TryFrom, marker::PhantomData};
+use std::{collections::HashSet, convert::TryFrom, marker::PhantomData};
 
 use proc_macro2::Span;
-use syn::{ext::IdentExt, Data, DeriveInput, Error, Ident, Result};
+use syn::{ext::IdentExt, DeriveInput, Error, Ident, Result};
 
 use super::{Table, TableField};
 use crate::{
     attrs::{parse_attrs, Insertable, TableAttr, TableFieldAttr},
     backend::Backend,
-    utils::{missing_attr, set_once},
+    utils::{
+        collect_all, missing_attr, named_fields, resolve_table_name, set_once, to_upper_camel_case,
+    },
 };
 
 macro_rules! none {
@@ -27,28 +29,65 @@ impl<B: Backend> TryFrom<&syn::Field> for TableField<B> {
             get_optional,
             get_many,
             set,
+            counter,
             default,
-            by_ref
+            by_ref,
+            sortable,
+            sql_type,
+            sql_default,
+            unique,
+            index,
+            references,
+            generate,
+            lookup,
+            skip
         );
         let mut insert_attrs = vec![];
 
-        for attr in parse_attrs::<TableFieldAttr>(&value.attrs)? {
+        let attrs = parse_attrs::<TableFieldAttr>(&value.attrs)?;
+        let attr_count = attrs.len();
+        for (span, attr) in attrs {
             match attr {
-                TableFieldAttr::Column(c) => set_once(&mut column, c)?,
-                TableFieldAttr::CustomType(..) => set_once(&mut custom_type, true)?,
-                TableFieldAttr::GetOne(g) => set_once(&mut get_one, g)?,
-                TableFieldAttr::GetOptional(g) => set_once(&mut get_optional, g)?,
-                TableFieldAttr::GetMany(g) => set_once(&mut get_many, g)?,
+                TableFieldAttr::Column(c) => set_once(&mut column, c, span)?,
+                TableFieldAttr::CustomType(..) => set_once(&mut custom_type, true, span)?,
+                TableFieldAttr::GetOne(g) => set_once(&mut get_one, g, span)?,
+                TableFieldAttr::GetOptional(g) => set_once(&mut get_optional, g, span)?,
+                TableFieldAttr::GetMany(g) => set_once(&mut get_many, g, span)?,
                 TableFieldAttr::Set(s) => {
-                    let default =
-                        || Ident::new(&format!("set_{}", ident.unraw()), Span::call_site());
-                    set_once(&mut set, s.unwrap_or_else(default))?
+                    let default = || Ident::new(&format!("set_{}", ident.unraw()), span);
+                    set_once(&mut set, s.unwrap_or_else(default), span)?
                 }
-                TableFieldAttr::Default(..) => set_once(&mut default, true)?,
-                TableFieldAttr::ByRef(..) => set_once(&mut by_ref, true)?,
+                TableFieldAttr::Counter(..) => set_once(&mut counter, span, span)?,
+                TableFieldAttr::Default(..) => set_once(&mut default, true, span)?,
+                TableFieldAttr::ByRef(..) => set_once(&mut by_ref, true, span)?,
+                TableFieldAttr::Sortable(..) => set_once(&mut sortable, span, span)?,
+                TableFieldAttr::SqlType(x) => set_once(&mut sql_type, x, span)?,
+                TableFieldAttr::SqlDefault(x) => set_once(&mut sql_default, x, span)?,
+                TableFieldAttr::Unique(..) => set_once(&mut unique, true, span)?,
+                TableFieldAttr::Index(..) => set_once(&mut index, true, span)?,
+                TableFieldAttr::References(x) => set_once(&mut references, x, span)?,
+                TableFieldAttr::Generate(x) => set_once(&mut generate, x, span)?,
+                TableFieldAttr::Lookup(x) => set_once(&mut lookup, x, span)?,
                 TableFieldAttr::InsertAttr(mut attr) => insert_attrs.append(&mut attr.0),
+                TableFieldAttr::Skip(..) => {
+                    if attr_count > 1 {
+                        return Err(Error::new(
+                            span,
+                            "#[ormx(skip)] can not be combined with other attributes",
+                        ));
+                    }
+                    set_once(&mut skip, true, span)?
+                }
             }
         }
+
+        if let (Some(true), Some(attr)) = (default, insert_attrs.first()) {
+            return Err(Error::new_spanned(
+                attr,
+                "#[ormx(insert_attribute)] has no effect on a field with #[ormx(default)]",
+            ));
+        }
+
         Ok(TableField {
             column_name: column.unwrap_or_else(|| ident.unraw().to_string()),
             field: ident,
@@ -59,7 +98,17 @@ impl<B: Backend> TryFrom<&syn::Field> for TableField<B> {
             get_optional,
             get_many,
             set,
+            counter,
             by_ref: by_ref.unwrap_or(false),
+            sortable,
+            sql_type,
+            sql_default,
+            unique: unique.unwrap_or(false),
+            index: index.unwrap_or(false),
+            references,
+            generate,
+            lookup,
+            skip: skip.unwrap_or(false),
             insert_attrs,
             _phantom: PhantomData,
         })
@@ -70,62 +119,209 @@ impl<B: Backend> TryFrom<&DeriveInput> for Table<B> {
     type Error = Error;
 
     fn try_from(value: &DeriveInput) -> Result<Self> {
-        let data = match &value.data {
-            Data::Struct(s) => s,
-            _ => panic!("not a struct with named fields"),
-        };
+        let (skipped, fields): (Vec<_>, Vec<_>) =
+            collect_all(named_fields(value)?.iter().map(TableField::try_from))?
+                .into_iter()
+                .partition(|field| field.skip);
 
-        let fields = data
-            .fields
-            .iter()
-            .map(TableField::try_from)
-            .collect::<Result<Vec<_>>>()?;
-
-        none!(table, id, insertable, deletable, order_by);
-        for attr in parse_attrs::<TableAttr>(&value.attrs)? {
+        none!(
+            table, schema, id, insertable, deletable, order_by, ddl, unchecked, cache, lock,
+            notify, queue
+        );
+        for (span, attr) in parse_attrs::<TableAttr>(&value.attrs)? {
             match attr {
-                TableAttr::Table(x) => set_once(&mut table, x)?,
-                TableAttr::Id(x) => set_once(&mut id, x)?,
+                TableAttr::Table(x) => set_once(&mut table, x, span)?,
+                TableAttr::Schema(x) => set_once(&mut schema, x, span)?,
+                TableAttr::Id(x) => set_once(&mut id, x, span)?,
                 TableAttr::Insertable(x) => {
                     let default = || Insertable {
                         attrs: vec![],
                         ident: Ident::new(&format!("Insert{}", value.ident), Span::call_site()),
                     };
-                    set_once(&mut insertable, x.unwrap_or_else(default))?;
+                    set_once(&mut insertable, x.unwrap_or_else(default), span)?;
                 }
-                TableAttr::Deletable(_) => set_once(&mut deletable, true)?,
-                TableAttr::OrderBy(by) => set_once(&mut order_by, by)?,
+                TableAttr::Deletable(_) => set_once(&mut deletable, true, span)?,
+                TableAttr::OrderBy(by) => set_once(&mut order_by, by, span)?,
+                TableAttr::Ddl(..) => set_once(&mut ddl, true, span)?,
+                TableAttr::Unchecked(..) => set_once(&mut unchecked, true, span)?,
+                TableAttr::Cache(..) => {
+                    if !value.generics.params.is_empty() {
+                        return Err(Error::new(
+                            span,
+                            "#[ormx(cache)] can not be used on a generic table",
+                        ));
+                    }
+                    set_once(&mut cache, true, span)?
+                }
+                TableAttr::Lock(..) => set_once(&mut lock, true, span)?,
+                TableAttr::Notify(x) => set_once(&mut notify, x, span)?,
+                TableAttr::Queue(x) => set_once(&mut queue, x, span)?,
+                // selected by `BackendKind::select`
+                TableAttr::Backend(_) => (),
             }
         }
 
-        let id = id.ok_or_else(|| missing_attr("id"))?;
+        let id = id.ok_or_else(|| missing_attr("id", value.ident.span()))?;
+        if skipped.iter().any(|field| field.field == id) {
+            return Err(Error::new(id.span(), "the id field can not be skipped"));
+        }
         let id = fields
             .iter()
             .find(|field| field.field == id)
-            .ok_or_else(|| {
-                Error::new(
-                    Span::call_site(),
-                    "id does not refer to a field of the struct",
-                )
-            })?
+            .ok_or_else(|| Error::new(id.span(), "id does not refer to a field of the struct"))?
             .clone();
 
-        if insertable.is_none() && fields.iter().any(|field| field.default) {
+        if let Some(set) = &id.set {
             return Err(Error::new(
-                Span::call_site(),
-                "#[ormx(default)] has no effect without #[ormx(insertable = ..)]",
+                set.span(),
+                "#[ormx(set)] can not be used on the id field",
             ));
         }
 
+        if let Some(span) = id.counter {
+            return Err(Error::new(
+                span,
+                "#[ormx(counter)] can not be used on the id field",
+            ));
+        }
+
+        if insertable.is_none() {
+            if let Some(field) = fields.iter().find(|field| field.default) {
+                return Err(Error::new(
+                    field.field.span(),
+                    "#[ormx(default)] has no effect without #[ormx(insertable = ..)]",
+                ));
+            }
+        }
+
+        check_generated_id(&id, &fields)?;
+        check_sort_variants(&value.ident, &fields)?;
+        check_column_variants(&value.ident, &fields)?;
+
+        if ddl.is_none() {
+            if let Some(field) = fields.iter().find(|field| field.has_ddl_attrs()) {
+                return Err(Error::new(
+                    field.field.span(),
+                    "#[ormx(sql_type, sql_default, unique, index, references)] have no effect without #[ormx(ddl)]",
+                ));
+            }
+        }
+
+        let table = table.ok_or_else(|| missing_attr("table", value.ident.span()))?;
+        let (schema, table) = resolve_table_name(schema, table)?;
+        let notify = notify.map(|channel| {
+            channel.unwrap_or_else(|| match &schema {
+                Some(schema) => format!("{schema}.{table}_changes"),
+                None => format!("{table}_changes"),
+            })
+        });
+
         Ok(Table {
             ident: value.ident.clone(),
             vis: value.vis.clone(),
-            table: table.ok_or_else(|| missing_attr("table"))?,
+            generics: value.generics.clone(),
+            schema,
+            table,
             id,
             insertable,
             fields,
+            skipped,
             deletable: deletable.unwrap_or(false),
             order_by,
+            ddl: ddl.unwrap_or(false),
+            unchecked: unchecked.unwrap_or(cfg!(feature = "unchecked")),
+            cache: cache.unwrap_or(false),
+            lock: lock.unwrap_or(false),
+            notify,
+            queue,
         })
     }
 }
+
+/// Checks that no two sortable fields map to the same variants of the sort enum, e.g `foo_1` and
+/// `foo1`, which would both become `Foo1Asc` and `Foo1Desc`.
+fn check_sort_variants<B: Backend>(ident: &Ident, fields: &[TableField<B>]) -> Result<()> {
+    let mut names = HashSet::new();
+    for field in fields {
+        let Some(span) = field.sortable else {
+            continue;
+        };
+        let name = to_upper_camel_case(&field.field.unraw().to_string());
+        if !names.insert(name.clone()) {
+            return Err(Error::new(
+                span,
+                format!("`{ident}Sort::{name}Asc` and `{ident}Sort::{name}Desc` are already generated for another field"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no two fields map to the same variant of the column enum.
+fn check_column_variants<B: Backend>(ident: &Ident, fields: &[TableField<B>]) -> Result<()> {
+    let mut names = HashSet::new();
+    for field in fields {
+        let name = to_upper_camel_case(&field.field.unraw().to_string());
+        if !names.insert(name.clone()) {
+            return Err(Error::new(
+                field.field.span(),
+                format!("`{ident}Column::{name}` is already generated for another field"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the usage of `#[ormx(generate)]` and `#[ormx(lookup)]`, which are only allowed on a
+/// database-generated ID of a backend which does not support `RETURNING`.
+fn check_generated_id<B: Backend>(id: &TableField<B>, fields: &[TableField<B>]) -> Result<()> {
+    let attr_span = |field: &TableField<B>| match (&field.generate, &field.lookup) {
+        (None, None) => None,
+        (Some(generate), _) => Some(generate.span()),
+        (_, Some(lookup)) => Some(lookup.span()),
+    };
+
+    if let Some(span) = fields
+        .iter()
+        .filter(|field| field.field != id.field)
+        .find_map(attr_span)
+    {
+        return Err(Error::new(
+            span,
+            "#[ormx(generate, lookup)] can only be used on the id field",
+        ));
+    }
+    let Some(span) = attr_span(id) else {
+        return Ok(());
+    };
+    if B::RETURNING {
+        return Err(Error::new(
+            span,
+            "#[ormx(generate, lookup)] have no effect on this backend, which returns the generated ID using RETURNING",
+        ));
+    }
+    if !id.default {
+        return Err(Error::new(
+            span,
+            "#[ormx(generate, lookup)] have no effect without #[ormx(default)]",
+        ));
+    }
+    if id.generate.is_some() && id.lookup.is_some() {
+        return Err(Error::new(
+            span,
+            "#[ormx(generate)] and #[ormx(lookup)] can not be used together",
+        ));
+    }
+    if let Some(lookup) = &id.lookup {
+        if !fields
+            .iter()
+            .any(|field| field.field == *lookup && !field.default)
+        {
+            return Err(Error::new(
+                lookup.span(),
+                "lookup does not refer to an insertable field of the struct",
+            ));
+        }
+    }
+    Ok(())
+}
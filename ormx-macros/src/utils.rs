@@ -1,5 +1,8 @@
 use proc_macro2::Span;
-use syn::{Error, Result};
+use syn::{
+    punctuated::Punctuated, token::Comma, Data, DeriveInput, Error, Field, Fields, Ident, LitStr,
+    Result,
+};
 
 macro_rules! stream {
     ($($t:tt)*) => {
@@ -8,16 +11,122 @@ macro_rules! stream {
 }
 pub(crate) use stream;
 
-pub fn set_once<T>(opt: &mut Option<T>, v: T) -> Result<()> {
+pub fn set_once<T>(opt: &mut Option<T>, v: T, span: Span) -> Result<()> {
     match opt.replace(v) {
         None => Ok(()),
-        Some(_) => Err(Error::new(Span::call_site(), "duplicate attribute")),
+        Some(_) => Err(Error::new(span, "duplicate attribute")),
     }
 }
 
-pub fn missing_attr(attr: &str) -> Error {
-    Error::new(
-        Span::call_site(),
-        format!(r#"missing #[ormx({})] attribute"#, attr),
-    )
+pub fn missing_attr(attr: &str, span: Span) -> Error {
+    Error::new(span, format!(r#"missing #[ormx({})] attribute"#, attr))
+}
+
+/// Creates an error for an unknown attribute, suggesting the most similar expected one.
+pub fn unknown_attr(ident: &Ident, expected: &[&str]) -> Error {
+    unknown("attribute", ident, expected)
+}
+
+/// Creates an error for an unknown `what`, e.g a backend, suggesting the most similar expected one.
+pub fn unknown(what: &str, ident: &Ident, expected: &[&str]) -> Error {
+    let name = ident.to_string();
+    let suggestion = expected
+        .iter()
+        .map(|candidate| (edit_distance(&name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= name.len().div_ceil(3))
+        .min_by_key(|(distance, _)| *distance);
+
+    let message = match suggestion {
+        Some((_, candidate)) => format!("unknown {what} `{name}`, did you mean `{candidate}`?"),
+        None => format!(
+            "unknown {what} `{name}`, expected one of: {}",
+            expected.join(", ")
+        ),
+    };
+    Error::new(ident.span(), message)
+}
+
+/// Converts a `snake_case` identifier to `UpperCamelCase`
+pub fn to_upper_camel_case(ident: &str) -> String {
+    ident
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect::<String>()
+        })
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns the fields of a struct with named fields, or an error pointing at the offending item.
+pub fn named_fields(input: &DeriveInput) -> Result<&Punctuated<Field, Comma>> {
+    const MESSAGE: &str = "ormx can only be derived for structs with named fields";
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            Fields::Unnamed(fields) => Err(Error::new_spanned(fields, MESSAGE)),
+            Fields::Unit => Err(Error::new(data.struct_token.span, MESSAGE)),
+        },
+        Data::Enum(data) => Err(Error::new(data.enum_token.span, MESSAGE)),
+        Data::Union(data) => Err(Error::new(data.union_token.span, MESSAGE)),
+    }
+}
+
+/// Like `collect::<Result<Vec<_>>>()`, but reports all errors instead of just the first one.
+pub fn collect_all<T>(iter: impl IntoIterator<Item = Result<T>>) -> Result<Vec<T>> {
+    let mut values = vec![];
+    let mut error: Option<Error> = None;
+    for result in iter {
+        match (result, &mut error) {
+            (Ok(value), _) => values.push(value),
+            (Err(err), Some(error)) => error.combine(err),
+            (Err(err), None) => error = Some(err),
+        }
+    }
+    match error {
+        Some(error) => Err(error),
+        None => Ok(values),
+    }
+}
+
+/// Resolves the schema and table name from `#[ormx(schema = ..)]` and `#[ormx(table = ..)]`.
+/// If no schema is given explicitly, a schema-qualified table name like `auth.users` is split
+/// into its parts.
+pub fn resolve_table_name(
+    schema: Option<LitStr>,
+    table: String,
+) -> Result<(Option<String>, String)> {
+    match (schema, table.split_once('.')) {
+        (Some(schema), Some(_)) => Err(Error::new(
+            schema.span(),
+            "the schema is specified both in #[ormx(schema)] and in the table name",
+        )),
+        (None, Some((schema, table))) => Ok((Some(schema.to_owned()), table.to_owned())),
+        (schema, None) => Ok((schema.map(|s| s.value()), table)),
+    }
 }
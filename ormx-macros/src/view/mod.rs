@@ -0,0 +1,46 @@
+use std::convert::TryFrom;
+
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Generics, Ident, Result, Visibility};
+
+use crate::{
+    backend::{dispatch, Backend, BackendKind},
+    table::TableField,
+};
+
+mod parse;
+
+pub struct View<B: Backend> {
+    pub ident: Ident,
+    pub vis: Visibility,
+    pub generics: Generics,
+    schema: Option<String>,
+    view: String,
+    pub fields: Vec<TableField<B>>,
+    pub order_by: Option<String>,
+    pub materialized: bool,
+    pub unchecked: bool,
+}
+
+impl<B: Backend> View<B> {
+    pub fn select_column_list(&self) -> String {
+        self.fields
+            .iter()
+            .map(|field| field.fmt_for_select(self.unchecked))
+            .join(", ")
+    }
+
+    pub fn name(&self) -> String {
+        B::quote_table(self.schema.as_deref(), &self.view)
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    dispatch!(BackendKind::select(&input)? => derive_with(input))
+}
+
+fn derive_with<B: Backend>(input: DeriveInput) -> Result<TokenStream> {
+    let parsed = View::<B>::try_from(&input)?;
+    Ok(B::impl_view(&parsed))
+}
@@ -0,0 +1,80 @@
+use std::convert::TryFrom;
+
+use syn::{DeriveInput, Error, Result};
+
+use super::View;
+use crate::{
+    attrs::{parse_attrs, ViewAttr},
+    backend::Backend,
+    table::TableField,
+    utils::{collect_all, missing_attr, named_fields, resolve_table_name, set_once},
+};
+
+impl<B: Backend> TryFrom<&DeriveInput> for View<B> {
+    type Error = Error;
+
+    fn try_from(value: &DeriveInput) -> Result<Self> {
+        let fields = collect_all(named_fields(value)?.iter().map(|field| {
+            let parsed = TableField::<B>::try_from(field)?;
+            let read_only = parsed.set.is_none()
+                && parsed.counter.is_none()
+                && !parsed.default
+                && !parsed.by_ref
+                && parsed.sortable.is_none()
+                && !parsed.has_ddl_attrs()
+                && parsed.generate.is_none()
+                && parsed.lookup.is_none()
+                && !parsed.skip
+                && parsed.insert_attrs.is_empty();
+            if read_only {
+                Ok(parsed)
+            } else {
+                Err(Error::new_spanned(
+                    &parsed.field,
+                    "#[ormx(set, counter, default, by_ref, sortable, generate, lookup, skip, insert_attribute)] and the DDL attributes \
+                     can not be used in a view, only #[ormx(column, custom_type, get_one, get_optional, get_many)]",
+                ))
+            }
+        }))?;
+
+        let mut view = None;
+        let mut schema = None;
+        let mut order_by = None;
+        let mut materialized = None;
+        let mut unchecked = None;
+        for (span, attr) in parse_attrs::<ViewAttr>(&value.attrs)? {
+            match attr {
+                ViewAttr::View(x) => set_once(&mut view, x, span)?,
+                ViewAttr::Schema(x) => set_once(&mut schema, x, span)?,
+                ViewAttr::OrderBy(x) => set_once(&mut order_by, x, span)?,
+                ViewAttr::Unchecked(_) => set_once(&mut unchecked, true, span)?,
+                // selected by `BackendKind::select`
+                ViewAttr::Backend(_) => (),
+                ViewAttr::Materialized(_) => {
+                    if !B::MATERIALIZED_VIEWS {
+                        return Err(Error::new(
+                            span,
+                            "materialized views are only supported by PostgreSQL",
+                        ));
+                    }
+                    set_once(&mut materialized, true, span)?
+                }
+            }
+        }
+
+        let view = view.ok_or_else(|| missing_attr("view", value.ident.span()))?;
+        let (schema, view) = resolve_table_name(schema, view)?;
+
+        Ok(View {
+            ident: value.ident.clone(),
+            vis: value.vis.clone(),
+            generics: value.generics.clone(),
+            schema,
+            view,
+            fields,
+            order_by,
+            materialized: materialized.unwrap_or(false),
+            unchecked: unchecked.unwrap_or(cfg!(feature = "unchecked")),
+        })
+    }
+}
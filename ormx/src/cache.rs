@@ -0,0 +1,394 @@
+//! Caching of rows for tables using `#[ormx(cache)]`.
+//!
+//! `Table::get` and the `get_one` getters of a cached table first look up the row in its cache.
+//! Only rows read using a `Pool` are cached, since a query executed on a connection may be part of
+//! a transaction which is rolled back later.
+//! The generated `update`, setters, counters, patches and `delete_row` invalidate the cached row.
+//! Rows written by other processes are only seen once their cache entry expires.
+
+use std::{
+    any::type_name,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{Duration, Instant},
+};
+
+use sqlx::{Executor, Pool};
+
+use crate::Table;
+
+/// A cache storing values of type `V` by their key `K`.
+pub trait Cache<K, V>: Send + Sync {
+    /// Returns a copy of the cached value, if present.
+    fn get(&self, key: &K) -> Option<V>;
+
+    /// Inserts a value, replacing a previously cached one.
+    fn insert(&self, key: K, value: V);
+
+    /// Removes a value from the cache.
+    fn remove(&self, key: &K);
+}
+
+/// A table using `#[ormx(cache)]`.
+/// Rows are returned from the cache by cloning them.
+///
+/// The cache is shared by the whole process, while changes made inside a transaction are only
+/// visible to that transaction until it's committed. Therefore, only rows read using a `Pool`
+/// are cached, while rows read using a connection or transaction (or a
+/// [ReplicaSet](crate::ReplicaSet), whose replicas may lag behind) are not.
+///
+/// Writes invalidate the cached row. A row which is read while it's being written is not cached,
+/// and neither is a row which has been written within the last [Cached::set_write_hold], since the
+/// transaction it has been written by may not be committed yet. Only transactions which take
+/// longer than that to commit after writing a row may therefore leave its previous state cached.
+pub trait Cached: Table<Id: Hash + Eq> + Clone {
+    #[doc(hidden)]
+    fn slot() -> &'static CacheSlot<Self>;
+
+    /// Sets how long a written row is not cached again, which defaults to [DEFAULT_WRITE_HOLD].
+    fn set_write_hold(hold: Duration) {
+        Self::slot().writes().hold = hold;
+    }
+
+    /// Starts a read using `db`, returning `None` if the row must not be cached.
+    #[doc(hidden)]
+    fn begin_fill<'c, E: Executor<'c>>(db: &E) -> Option<Fill> {
+        is_pool(db).then(|| Fill(Self::slot().writes().epoch))
+    }
+
+    /// Caches a row returned by a read started using [Cached::begin_fill], unless the row has been
+    /// written in the meantime. Returns true if the row has been cached.
+    #[doc(hidden)]
+    fn fill(fill: Option<Fill>, row: &Self) -> bool {
+        let Some(Fill(epoch)) = fill else {
+            return false;
+        };
+        let id = row.id();
+        // no write may happen between the check and the insert
+        let writes = Self::slot().writes();
+        if !writes.may_fill(epoch, &id) {
+            return false;
+        }
+        Self::cache().insert(id, row.clone());
+        true
+    }
+
+    /// Removes a written row from the cache.
+    #[doc(hidden)]
+    fn invalidate(id: Self::Id) {
+        let mut writes = Self::slot().writes();
+        writes.write(id);
+        Self::cache().remove(&id);
+    }
+
+    /// Returns the cache of this table.
+    /// Unless replaced using [Cached::set_cache], this is an [LruCache] with a capacity of
+    /// [LruCache::DEFAULT_CAPACITY] rows, which expire after [LruCache::DEFAULT_TTL].
+    fn cache() -> &'static dyn Cache<Self::Id, Self> {
+        Self::slot().get()
+    }
+
+    /// Replaces the cache of this table.
+    /// Returns `false` if the cache has already been used, in which case it is not replaced.
+    fn set_cache(cache: impl Cache<Self::Id, Self> + 'static) -> bool {
+        Self::slot().set(Box::new(cache))
+    }
+}
+
+/// An in-process cache, evicting the least recently used entry once its capacity is exceeded.
+/// Optionally, entries expire after a fixed duration.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    inner: Mutex<Lru<K, V>>,
+}
+
+struct Lru<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    // keys ordered by their last use
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    used: u64,
+}
+
+impl<K, V> LruCache<K, V> {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+    /// Creates a cache holding at most `capacity` entries, which never expire.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ttl: None,
+            inner: Mutex::new(Lru {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Lets entries expire after the given duration.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lru<K, V>> {
+        // the cache stays consistent even if another thread panicked while holding the lock
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<K, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY).with_ttl(Self::DEFAULT_TTL)
+    }
+}
+
+impl<K, V> Cache<K, V> for LruCache<K, V>
+where
+    K: Hash + Eq + Clone + Send,
+    V: Clone + Send,
+{
+    fn get(&self, key: &K) -> Option<V> {
+        let mut lru = self.lock();
+        let Lru {
+            entries,
+            order,
+            tick,
+        } = &mut *lru;
+
+        let entry = entries.get_mut(key)?;
+        if self.ttl.is_some_and(|ttl| entry.inserted.elapsed() > ttl) {
+            order.remove(&entry.used);
+            entries.remove(key);
+            return None;
+        }
+        order.remove(&entry.used);
+        *tick += 1;
+        entry.used = *tick;
+        order.insert(*tick, key.clone());
+        Some(entry.value.clone())
+    }
+
+    fn insert(&self, key: K, value: V) {
+        let mut lru = self.lock();
+        if let Some(old) = lru.entries.remove(&key) {
+            lru.order.remove(&old.used);
+        }
+        lru.tick += 1;
+        let used = lru.tick;
+        lru.order.insert(used, key.clone());
+        lru.entries.insert(
+            key,
+            Entry {
+                value,
+                inserted: Instant::now(),
+                used,
+            },
+        );
+        while lru.entries.len() > self.capacity {
+            match lru.order.pop_first() {
+                Some((_, key)) => lru.entries.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    fn remove(&self, key: &K) {
+        let mut lru = self.lock();
+        if let Some(old) = lru.entries.remove(key) {
+            lru.order.remove(&old.used);
+        }
+    }
+}
+
+/// How long a written row is not cached again by default, see [Cached].
+pub const DEFAULT_WRITE_HOLD: Duration = Duration::from_secs(10);
+
+/// Returns true if `db` is a pool, whose queries are never part of a transaction.
+/// Since `Executor` is implemented for `&Pool`, connections and ormx's replica sets, but never
+/// for a reference to a reference, the type name is enough to tell a pool apart.
+fn is_pool<'c, E: Executor<'c>>(_: &E) -> bool {
+    type_name::<E>() == type_name::<&Pool<E::Database>>()
+}
+
+/// Allows caching the row returned by a read, see [Cached::begin_fill].
+#[doc(hidden)]
+pub struct Fill(u64);
+
+/// Tracks the writes to the rows of a table, so that rows read while being written are not cached
+/// in their previous state.
+struct Writes<Id> {
+    // incremented by every write
+    epoch: u64,
+    // the time at which each recently written row has been written
+    written: HashMap<Id, Instant>,
+    hold: Duration,
+}
+
+impl<Id: Hash + Eq> Writes<Id> {
+    fn new() -> Self {
+        Self {
+            epoch: 0,
+            written: HashMap::new(),
+            hold: DEFAULT_WRITE_HOLD,
+        }
+    }
+
+    fn write(&mut self, id: Id) {
+        self.epoch += 1;
+        let hold = self.hold;
+        self.written.retain(|_, at| at.elapsed() < hold);
+        self.written.insert(id, Instant::now());
+    }
+
+    /// Returns true if a row read since `epoch` may be cached.
+    fn may_fill(&self, epoch: u64, id: &Id) -> bool {
+        self.epoch == epoch
+            && self
+                .written
+                .get(id)
+                .is_none_or(|at| at.elapsed() >= self.hold)
+    }
+}
+
+/// Holds the cache of a table, which is initialized on first use.
+#[doc(hidden)]
+pub struct CacheSlot<T: Table> {
+    cache: OnceLock<Box<dyn Cache<T::Id, T>>>,
+    writes: OnceLock<Mutex<Writes<T::Id>>>,
+}
+
+impl<T: Cached> CacheSlot<T> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            cache: OnceLock::new(),
+            writes: OnceLock::new(),
+        }
+    }
+
+    fn get(&self) -> &dyn Cache<T::Id, T> {
+        &**self.cache.get_or_init(|| Box::new(LruCache::default()))
+    }
+
+    fn set(&self, cache: Box<dyn Cache<T::Id, T>>) -> bool {
+        self.cache.set(cache).is_ok()
+    }
+
+    fn writes(&self) -> MutexGuard<'_, Writes<T::Id>> {
+        let writes = self.writes.get_or_init(|| Mutex::new(Writes::new()));
+        writes.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Maps the values of a unique column to the IDs of their rows, used by cached `get_one` getters.
+/// Since rows may change, the row found through an index has to be checked before returning it.
+#[doc(hidden)]
+pub struct CacheIndex<K, Id>(OnceLock<LruCache<K, Id>>);
+
+impl<K, Id> CacheIndex<K, Id>
+where
+    K: Hash + Eq + Clone + Send,
+    Id: Clone + Send,
+{
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    pub fn get(&self) -> &LruCache<K, Id> {
+        self.0.get_or_init(LruCache::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use super::{Cache, LruCache, Writes};
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        // using 1 makes 2 the least recently used entry
+        assert_eq!(cache.get(&1), Some("a"));
+        cache.insert(3, "c");
+
+        assert_eq!(cache.get(&1), Some("a"));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&3), Some("c"));
+    }
+
+    #[test]
+    fn replacing_does_not_evict() {
+        let cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        cache.insert(1, "c");
+
+        assert_eq!(cache.get(&1), Some("c"));
+        assert_eq!(cache.get(&2), Some("b"));
+    }
+
+    #[test]
+    fn remove() {
+        let cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.remove(&1);
+        cache.remove(&2);
+
+        assert_eq!(cache.get(&1), None);
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let cache = LruCache::new(2).with_ttl(Duration::from_millis(20));
+        cache.insert(1, "a");
+        assert_eq!(cache.get(&1), Some("a"));
+
+        sleep(Duration::from_millis(40));
+        assert_eq!(cache.get(&1), None);
+        // the expired entry no longer takes up capacity
+        cache.insert(2, "b");
+        cache.insert(3, "c");
+        assert_eq!(cache.get(&2), Some("b"));
+        assert_eq!(cache.get(&3), Some("c"));
+    }
+
+    #[test]
+    fn writes_during_a_read_prevent_filling() {
+        let mut writes = Writes::new();
+        let epoch = writes.epoch;
+        assert!(writes.may_fill(epoch, &1));
+
+        writes.write(2);
+        // any write may have changed the row, since the read might have been for a unique field
+        assert!(!writes.may_fill(epoch, &1));
+        assert!(writes.may_fill(writes.epoch, &1));
+    }
+
+    #[test]
+    fn written_rows_are_held() {
+        let mut writes = Writes::new();
+        writes.hold = Duration::from_millis(20);
+        writes.write(1);
+        assert!(!writes.may_fill(writes.epoch, &1));
+
+        sleep(Duration::from_millis(40));
+        assert!(writes.may_fill(writes.epoch, &1));
+        // expired holds are dropped by the next write
+        writes.write(2);
+        assert_eq!(writes.written.len(), 1);
+    }
+}
@@ -0,0 +1,254 @@
+//! Bulk loading of rows using `COPY .. FROM STDIN (FORMAT binary)`, used by the generated
+//! `copy_in` of insert structs.
+//!
+//! Every value is written in the binary format of its `sqlx::Encode` implementation, which has to
+//! match the type of its column exactly (e.g an `i32` can not be copied into a `BIGINT` column).
+//!
+//! Arrays and composite types contain the OIDs of their elements and fields. sqlx leaves these
+//! empty for custom types and only fills them in when binding a value to a query, so the types of
+//! the columns are looked up before the `COPY` starts, and the empty OIDs are filled in using them.
+
+use futures::future::BoxFuture;
+use sqlx::{
+    encode::IsNull,
+    postgres::{types::Oid, PgArgumentBuffer, PgCopyIn},
+    Column, Encode, Executor, PgConnection, Postgres, Result, Statement,
+};
+
+// signature, followed by the flags and the length of the header extension
+const HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+const TRAILER: i16 = -1;
+// encoded rows are buffered until they exceed this size
+const FLUSH_THRESHOLD: usize = 64 * 1024;
+// types with a lower OID are built into the database
+const FIRST_NORMAL_OID: u32 = 16384;
+
+/// Encodes rows in the binary `COPY` format and sends them to the database.
+#[doc(hidden)]
+pub struct BinaryCopy<'c> {
+    copy: PgCopyIn<&'c mut PgConnection>,
+    buf: Vec<u8>,
+    columns: Vec<Shape>,
+    column: usize,
+}
+
+impl<'c> BinaryCopy<'c> {
+    /// Starts the `COPY`. `select` has to select the copied columns, and is used to look up their
+    /// types.
+    pub async fn begin(conn: &'c mut PgConnection, statement: &str, select: &str) -> Result<Self> {
+        let oids: Vec<Option<Oid>> = conn
+            .prepare(select)
+            .await?
+            .columns()
+            .iter()
+            .map(|column| column.type_info().oid())
+            .collect();
+        let mut columns = Vec::with_capacity(oids.len());
+        for oid in oids {
+            columns.push(match oid {
+                Some(oid) => Shape::describe(conn, oid).await?,
+                None => Shape::Scalar,
+            });
+        }
+
+        Ok(Self {
+            copy: conn.copy_in_raw(statement).await?,
+            buf: HEADER.to_vec(),
+            columns,
+            column: 0,
+        })
+    }
+
+    /// Starts a new row, which is followed by exactly `fields` calls to [BinaryCopy::field].
+    pub fn row(&mut self, fields: i16) {
+        self.buf.extend_from_slice(&fields.to_be_bytes());
+        self.column = 0;
+    }
+
+    pub fn field<'q, T: Encode<'q, Postgres>>(&mut self, value: &T) -> Result<()> {
+        let mut value_buf = PgArgumentBuffer::default();
+        let is_null = value
+            .encode_by_ref(&mut value_buf)
+            .map_err(sqlx::Error::Encode)?;
+        let shape = self.columns.get(self.column).unwrap_or(&Shape::Scalar);
+        self.column += 1;
+        match is_null {
+            IsNull::Yes => self.buf.extend_from_slice(&(-1i32).to_be_bytes()),
+            IsNull::No => {
+                // a malformed value is left for the database to reject
+                let _ = shape.patch(&mut value_buf);
+                let len = i32::try_from(value_buf.len())
+                    .map_err(|_| sqlx::Error::Encode("value too large to copy".into()))?;
+                self.buf.extend_from_slice(&len.to_be_bytes());
+                self.buf.extend_from_slice(&value_buf);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the buffered rows once they exceed the flush threshold.
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.buf.len() >= FLUSH_THRESHOLD {
+            self.copy.send(self.buf.as_slice()).await?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    /// Sends the remaining rows and completes the `COPY`, returning the number of rows written.
+    pub async fn finish(mut self) -> Result<u64> {
+        self.buf.extend_from_slice(&TRAILER.to_be_bytes());
+        self.copy.send(self.buf.as_slice()).await?;
+        self.copy.finish().await
+    }
+}
+
+/// The structure of the binary format of a type, as far as it contains OIDs.
+enum Shape {
+    Scalar,
+    Array(Oid, Box<Shape>),
+    Record(Vec<(Oid, Shape)>),
+}
+
+impl Shape {
+    fn describe(conn: &mut PgConnection, oid: Oid) -> BoxFuture<'_, Result<Self>> {
+        Box::pin(async move {
+            if oid.0 < FIRST_NORMAL_OID {
+                return Ok(Shape::Scalar);
+            }
+
+            let (kind, element, base, relation): (i8, Oid, Oid, Oid) = sqlx::query_as(
+                "SELECT typtype, CASE WHEN typlen = -1 THEN typelem ELSE 0 END, typbasetype, typrelid
+                 FROM pg_type WHERE oid = $1",
+            )
+            .bind(oid)
+            .fetch_one(&mut *conn)
+            .await?;
+
+            if element.0 != 0 {
+                let shape = Shape::describe(conn, element).await?;
+                return Ok(Shape::Array(element, Box::new(shape)));
+            }
+            match kind as u8 {
+                b'd' => Shape::describe(conn, base).await,
+                b'c' => {
+                    let field_oids: Vec<Oid> = sqlx::query_scalar(
+                        "SELECT atttypid FROM pg_attribute
+                         WHERE attrelid = $1 AND attnum > 0 AND NOT attisdropped
+                         ORDER BY attnum",
+                    )
+                    .bind(relation)
+                    .fetch_all(&mut *conn)
+                    .await?;
+                    let mut fields = Vec::with_capacity(field_oids.len());
+                    for oid in field_oids {
+                        fields.push((oid, Shape::describe(conn, oid).await?));
+                    }
+                    Ok(Shape::Record(fields))
+                }
+                _ => Ok(Shape::Scalar),
+            }
+        })
+    }
+
+    /// Fills in the empty OIDs in the binary encoding `value`.
+    /// Returns `None` if `value` does not match the shape.
+    fn patch(&self, value: &mut [u8]) -> Option<()> {
+        match self {
+            Shape::Scalar => {}
+            // dimensions, flags and element type, followed by the size and lower bound of every
+            // dimension and the elements
+            Shape::Array(element, shape) => {
+                let dimensions = usize::try_from(read_i32(value, 0)?).ok()?;
+                fill_oid(value, 8, *element)?;
+                let mut offset = 12 + 8 * dimensions;
+                while offset < value.len() {
+                    offset = shape.patch_field(value, offset)?;
+                }
+            }
+            // number of fields, followed by the type and value of every field
+            Shape::Record(fields) => {
+                let mut offset = 4;
+                for (oid, shape) in fields {
+                    if offset >= value.len() {
+                        break;
+                    }
+                    fill_oid(value, offset, *oid)?;
+                    offset = shape.patch_field(value, offset + 4)?;
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Patches the length-prefixed value at `offset`, returning the offset following it.
+    fn patch_field(&self, value: &mut [u8], offset: usize) -> Option<usize> {
+        let start = offset + 4;
+        let Ok(len) = usize::try_from(read_i32(value, offset)?) else {
+            // NULL
+            return Some(start);
+        };
+        let end = start.checked_add(len)?;
+        self.patch(value.get_mut(start..end)?)?;
+        Some(end)
+    }
+}
+
+fn read_i32(value: &[u8], offset: usize) -> Option<i32> {
+    let bytes = value.get(offset..offset + 4)?;
+    Some(i32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn fill_oid(value: &mut [u8], offset: usize, oid: Oid) -> Option<()> {
+    let bytes = value.get_mut(offset..offset + 4)?;
+    if bytes == [0; 4] {
+        bytes.copy_from_slice(&oid.0.to_be_bytes());
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::postgres::types::Oid;
+
+    use super::Shape;
+
+    #[test]
+    fn fills_empty_oids() {
+        let shape = Shape::Array(
+            Oid(20000),
+            Box::new(Shape::Record(vec![
+                (Oid(23), Shape::Scalar),
+                (Oid(20001), Shape::Scalar),
+            ])),
+        );
+        let record: &[u8] = &[
+            0, 0, 0, 2, // fields
+            0, 0, 0, 23, 0, 0, 0, 4, 0, 0, 0, 1, // integer
+            0, 0, 0, 0, 0, 0, 0, 1, b'a', // custom type
+        ];
+        let mut array = vec![
+            0, 0, 0, 1, // dimensions
+            0, 0, 0, 1, // flags
+            0, 0, 0, 0, // element type
+            0, 0, 0, 2, 0, 0, 0, 1, // size and lower bound
+            0, 0, 0, 25, // element
+        ];
+        array.extend_from_slice(record);
+        array.extend_from_slice(&[255, 255, 255, 255]); // NULL
+
+        shape.patch(&mut array).unwrap();
+        assert_eq!(array[8..12], 20000u32.to_be_bytes());
+        assert_eq!(array[40..44], 20001u32.to_be_bytes());
+        // OIDs which are set are kept
+        assert_eq!(array[28..32], 23u32.to_be_bytes());
+    }
+
+    #[test]
+    fn rejects_truncated_values() {
+        let shape = Shape::Record(vec![(Oid(20000), Shape::Scalar)]);
+        assert!(shape
+            .patch(&mut [0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 8])
+            .is_none());
+    }
+}
@@ -0,0 +1,188 @@
+//! Generation of `CREATE TABLE` statements for tables using `#[ormx(ddl)]`.
+
+use std::fmt::Write;
+
+/// The SQL dialect to generate DDL for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    Postgres,
+    MySql,
+    MariaDb,
+}
+
+/// SQL type of a column, either inferred from the type of the field or set explicitly using
+/// `#[ormx(sql_type = "..")]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SqlType {
+    Boolean,
+    TinyInt,
+    SmallInt,
+    Integer,
+    BigInt,
+    UnsignedTinyInt,
+    UnsignedSmallInt,
+    UnsignedInteger,
+    UnsignedBigInt,
+    Real,
+    Double,
+    Decimal,
+    Text,
+    Bytes,
+    Uuid,
+    Json,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Custom(&'static str),
+}
+
+/// A column of a table, as described by `derive(Table)`.
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: &'static str,
+    pub sql_type: SqlType,
+    pub nullable: bool,
+    pub primary_key: bool,
+    /// The value of this column is generated by the database.
+    pub generated: bool,
+    pub default: Option<&'static str>,
+    pub unique: bool,
+    pub index: bool,
+    pub references: Option<&'static str>,
+}
+
+impl Dialect {
+    /// Quotes an identifier, e.g a table or column name.
+    pub fn quote(self, ident: &str) -> String {
+        let q = match self {
+            Dialect::Postgres => '"',
+            Dialect::MySql | Dialect::MariaDb => '`',
+        };
+        let escaped = ident.replace(q, &format!("{q}{q}"));
+        format!("{q}{escaped}{q}")
+    }
+}
+
+impl SqlType {
+    /// Returns the name of this type in the given dialect.
+    pub fn name(self, dialect: Dialect) -> &'static str {
+        use SqlType::*;
+
+        let postgres = dialect == Dialect::Postgres;
+        match self {
+            Boolean => "BOOLEAN",
+            TinyInt if postgres => "SMALLINT",
+            TinyInt => "TINYINT",
+            SmallInt => "SMALLINT",
+            Integer if postgres => "INTEGER",
+            Integer => "INT",
+            BigInt => "BIGINT",
+            UnsignedTinyInt if postgres => "SMALLINT",
+            UnsignedTinyInt => "TINYINT UNSIGNED",
+            UnsignedSmallInt if postgres => "INTEGER",
+            UnsignedSmallInt => "SMALLINT UNSIGNED",
+            UnsignedInteger if postgres => "BIGINT",
+            UnsignedInteger => "INT UNSIGNED",
+            UnsignedBigInt if postgres => "NUMERIC(20)",
+            UnsignedBigInt => "BIGINT UNSIGNED",
+            Real if postgres => "REAL",
+            Real => "FLOAT",
+            Double if postgres => "DOUBLE PRECISION",
+            Double => "DOUBLE",
+            Decimal if postgres => "NUMERIC",
+            Decimal => "DECIMAL(65, 30)",
+            Text if postgres => "TEXT",
+            Text => "VARCHAR(255)",
+            Bytes if postgres => "BYTEA",
+            Bytes => "BLOB",
+            Uuid if postgres => "UUID",
+            Uuid => "BINARY(16)",
+            Json if postgres => "JSONB",
+            Json => "JSON",
+            Date => "DATE",
+            Time => "TIME",
+            Timestamp if postgres => "TIMESTAMP",
+            Timestamp => "DATETIME",
+            TimestampTz if postgres => "TIMESTAMPTZ",
+            TimestampTz => "TIMESTAMP",
+            Custom(name) => name,
+        }
+    }
+
+    /// Returns the name of the auto-incrementing variant of this type for PostgreSQL.
+    fn serial(self) -> Option<&'static str> {
+        match self {
+            SqlType::TinyInt | SqlType::SmallInt | SqlType::UnsignedTinyInt => Some("SMALLSERIAL"),
+            SqlType::Integer | SqlType::UnsignedSmallInt => Some("SERIAL"),
+            SqlType::BigInt | SqlType::UnsignedInteger => Some("BIGSERIAL"),
+            _ => None,
+        }
+    }
+}
+
+/// Builds the `CREATE TABLE` statement for a table, followed by a `CREATE INDEX` statement for
+/// every column with `#[ormx(index)]`.
+pub fn create_table(
+    dialect: Dialect,
+    schema: Option<&str>,
+    table: &str,
+    columns: &[Column],
+) -> String {
+    let table_name = match schema {
+        Some(schema) => format!("{}.{}", dialect.quote(schema), dialect.quote(table)),
+        None => dialect.quote(table),
+    };
+
+    let mut sql = format!("CREATE TABLE {table_name}\n(\n");
+    for (i, column) in columns.iter().enumerate() {
+        let separator = if i + 1 == columns.len() { "" } else { "," };
+        writeln!(sql, "    {}{separator}", column_definition(dialect, column)).unwrap();
+    }
+    sql.push_str(");\n");
+
+    for column in columns.iter().filter(|column| column.index) {
+        writeln!(
+            sql,
+            "CREATE INDEX {} ON {table_name} ({});",
+            dialect.quote(&format!("{table}_{}_idx", column.name)),
+            dialect.quote(column.name),
+        )
+        .unwrap();
+    }
+
+    sql
+}
+
+fn column_definition(dialect: Dialect, column: &Column) -> String {
+    // only a generated primary key is auto-incrementing, other generated columns need a default
+    let auto_increment = column.generated && column.primary_key && column.default.is_none();
+    let serial = match (dialect, auto_increment) {
+        (Dialect::Postgres, true) => column.sql_type.serial(),
+        _ => None,
+    };
+
+    let mut sql = format!(
+        "{} {}",
+        dialect.quote(column.name),
+        serial.unwrap_or_else(|| column.sql_type.name(dialect))
+    );
+    if !column.nullable {
+        sql.push_str(" NOT NULL");
+    }
+    if let Some(default) = column.default {
+        write!(sql, " DEFAULT {default}").unwrap();
+    } else if auto_increment && dialect != Dialect::Postgres {
+        sql.push_str(" AUTO_INCREMENT");
+    }
+    if column.primary_key {
+        sql.push_str(" PRIMARY KEY");
+    }
+    if column.unique {
+        sql.push_str(" UNIQUE");
+    }
+    if let Some(references) = column.references {
+        write!(sql, " REFERENCES {references}").unwrap();
+    }
+    sql
+}
@@ -32,28 +32,117 @@
 //! }
 //! ```
 //!
+//! # Example: Projection
+//! ```rust,ignore
+//! #[derive(ormx::Projection)]
+//! #[ormx(table = User)]
+//! struct UserSummary {
+//!     user_id: u32,
+//!     email: String,
+//! }
+//! ```
+//!
+//! # Example: Schema per tenant (PostgreSQL)
+//! ```rust,ignore
+//! let mut tx = ormx::begin_in_schema(&pool, "tenant_42").await?;
+//! let user = User::get(&mut *tx, id).await?;
+//! tx.commit().await?;
+//! ```
+//!
+//! # Example: Read replicas
+//! ```rust,ignore
+//! let db = ormx::ReplicaSet::new(primary, [replica_a, replica_b]);
+//! // reads are executed on a replica, writes on the primary
+//! let mut user = User::get(&db, id).await?;
+//! user.set_last_login(&db, Some(now)).await?;
+//!
+//! // once something has been written, all following queries go to the primary
+//! let session = db.read_your_writes();
+//! user.set_last_login(&session, None).await?;
+//! let user = User::get(&session, id).await?;
+//! ```
+//!
+//! # Example: Retried transactions
+//! ```rust,ignore
+//! // retried on serialization failures and deadlocks, waiting in between (see `Retry`)
+//! let user = ormx::transaction(&pool, |tx| {
+//!     Box::pin(async move {
+//!         let mut user = User::get(&mut **tx, id).await?;
+//!         user.set_last_login(&mut **tx, Some(now)).await?;
+//!         Ok::<_, sqlx::Error>(user)
+//!     })
+//! })
+//! .await?;
+//! ```
+//!
+//! # Example: Verifying the schema at startup
+//! ```rust,ignore
+//! // checks all tables deriving `Table` against the database
+//! ormx::verify_all(&pool).await?;
+//! ```
+//!
 //! # Documentation
 //! See the docs of [derive(Table)](derive.Table.html) and [Patch](trait.Patch.html).
 
 use std::future::Future;
 
+pub use cache::{Cache, Cached, LruCache, DEFAULT_WRITE_HOLD};
+pub use ddl::Dialect;
 use futures::{Stream, TryStreamExt};
+pub use meta::{ColumnMeta, TableMeta};
 pub use ormx_macros::*;
-use sqlx::{Executor, Result};
+use sqlx::{Acquire, Executor, Result};
 
 #[doc(hidden)]
 pub mod exports {
-    pub use futures::Stream;
+    pub use futures::{Stream, TryStreamExt};
+    pub use inventory;
 
+    pub use crate::cache::{CacheIndex, CacheSlot, Fill};
+    #[cfg(feature = "postgres")]
+    pub use crate::copy::BinaryCopy;
+    #[cfg(feature = "postgres")]
+    pub use crate::notify::{changes, changes_from_pool};
     pub use crate::query2::map::*;
+    #[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+    pub use crate::verify::Registration;
 }
 
+mod cache;
+#[cfg(feature = "postgres")]
+mod copy;
+pub mod ddl;
+mod meta;
+#[cfg(feature = "postgres")]
+mod notify;
 #[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
 mod query2;
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+mod replica;
+#[cfg(feature = "postgres")]
+mod tenant;
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+mod transaction;
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+mod verify;
+#[cfg(feature = "postgres")]
+pub use notify::Change;
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+pub use replica::{ReadYourWrites, ReplicaSet, Router};
+#[cfg(feature = "postgres")]
+pub use tenant::{begin_in_schema, set_search_path};
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+pub use transaction::{
+    is_retryable, transaction, transaction_with, Retry, Sleep, TransactionError,
+};
+#[cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+pub use verify::{verify_all, verify_schema, Introspect, Mismatch, SchemaError};
 
-#[cfg(any(feature = "mysql", feature = "mariadb"))]
+/// The database of the enabled backend.
+/// When multiple backends are enabled, use [Table::Database] instead.
+#[cfg(all(any(feature = "mysql", feature = "mariadb"), not(feature = "postgres")))]
 pub type Db = sqlx::MySql;
-#[cfg(feature = "postgres")]
+#[cfg(all(feature = "postgres", not(any(feature = "mysql", feature = "mariadb"))))]
 pub type Db = sqlx::Postgres;
 #[cfg(feature = "sqlite")]
 pub type Db = sqlx::Sqlite;
@@ -63,6 +152,9 @@ pub trait Table
 where
     Self: Sized + Send + Sync + 'static,
 {
+    /// The database this table lives in, e.g `sqlx::Postgres`.
+    type Database: sqlx::Database;
+
     /// Type of the ID column of this table.
     type Id: 'static + Copy + Send;
 
@@ -71,8 +163,7 @@ where
 
     /// Insert a row into the database.
     fn insert<'a, 'c: 'a>(
-        #[cfg(not(feature = "mysql"))] db: impl Executor<'c, Database = Db> + 'a,
-        #[cfg(feature = "mysql")] db: &'c mut sqlx::MySqlConnection,
+        db: impl Acquire<'c, Database = Self::Database> + Send + 'a,
         row: impl Insert<Table = Self>,
     ) -> impl Future<Output = Result<Self>> + Send + 'a {
         row.insert(db)
@@ -80,22 +171,31 @@ where
 
     /// Queries the row of the given id.
     fn get<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
         id: Self::Id,
     ) -> impl Future<Output = Result<Self>> + Send + 'a;
 
+    /// Queries the row of the given id, bypassing the cache of this table.
+    #[doc(hidden)]
+    fn get_uncached<'a, 'c: 'a>(
+        db: impl Executor<'c, Database = Self::Database> + 'a,
+        id: Self::Id,
+    ) -> impl Future<Output = Result<Self>> + Send + 'a {
+        Self::get(db, id)
+    }
+
     /// Stream all rows from this table.
     /// By default, results are ordered in descending order according to their ID column.
     /// This can be configured using `#[ormx(order_by = "some_column ASC")]`.
     fn stream_all<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Stream<Item = Result<Self>> + Send + 'a;
 
     /// Streams at most `limit` rows from this table, skipping the first `offset` rows.
     /// By default, results are ordered in descending order according to their ID column.
     /// This can be configured using `#[ormx(order_by = "some_column ASC")]`.
     fn stream_all_paginated<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
         offset: i64,
         limit: i64,
     ) -> impl Stream<Item = Result<Self>> + Send + 'a;
@@ -104,7 +204,7 @@ where
     /// By default, results are ordered in descending order according to their ID column.
     /// This can be configured using `#[ormx(order_by = "some_column ASC")]`.
     fn all<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Future<Output = Result<Vec<Self>>> + Send + 'a {
         Self::stream_all(db).try_collect()
     }
@@ -113,7 +213,7 @@ where
     /// By default, results are ordered in descending order according to their ID column.
     /// This can be configured using `#[ormx(order_by = "some_column ASC")]`.
     fn all_paginated<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
         offset: i64,
         limit: i64,
     ) -> impl Future<Output = Result<Vec<Self>>> + Send + 'a {
@@ -123,7 +223,7 @@ where
     /// Applies a patch to this row.
     fn patch<'a, 'c: 'a, P>(
         &'a mut self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
         patch: P,
     ) -> impl Future<Output = Result<()>> + Send + 'a
     where
@@ -137,18 +237,25 @@ where
         }
     }
 
+    /// Removes the row of the given id from the cache of this table.
+    /// This does nothing unless the table uses `#[ormx(cache)]`.
+    fn invalidate_cached(id: Self::Id) {
+        let _ = id;
+    }
+
     /// Updates all fields of this row, regardless if they have been changed or not.
     fn update<'a, 'c: 'a>(
         &'a self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Future<Output = Result<()>> + Send + 'a;
 
     /// Refresh this row, querying all columns from the database.
     fn reload<'a, 'c: 'a>(
         &'a mut self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         async move {
+            Self::invalidate_cached(self.id());
             *self = Self::get(db, self.id()).send().await?;
             Ok(())
         }
@@ -161,14 +268,14 @@ where
 {
     /// Delete a row from the database
     fn delete_row<'a, 'c: 'a>(
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
         id: Self::Id,
     ) -> impl Future<Output = Result<()>> + Send + 'a;
 
     /// Deletes this row from the database
     fn delete<'a, 'c: 'a>(
         self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         Self::delete_row(db, self.id())
     }
@@ -176,7 +283,7 @@ where
     /// Deletes this row from the database
     fn delete_ref<'a, 'c: 'a>(
         &self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = Self::Database> + 'a,
     ) -> impl Future<Output = Result<()>> + Send + 'a {
         Self::delete_row(db, self.id())
     }
@@ -196,7 +303,7 @@ where
     /// Applies this patch to a row in the database.
     fn patch_row<'a, 'c: 'a>(
         &'a self,
-        db: impl Executor<'c, Database = Db> + 'a,
+        db: impl Executor<'c, Database = <Self::Table as Table>::Database> + 'a,
         id: <Self::Table as Table>::Id,
     ) -> impl Future<Output = Result<()>> + Send + 'a;
 }
@@ -209,10 +316,10 @@ where
     type Table: Table;
 
     /// Insert a row into the database, returning the inserted row.
+    /// `db` may be a pool, a connection or a transaction.
     fn insert<'a, 'c: 'a>(
         self,
-        #[cfg(not(feature = "mysql"))] db: impl Executor<'c, Database = Db> + 'a,
-        #[cfg(feature = "mysql")] db: &'c mut sqlx::MySqlConnection,
+        db: impl Acquire<'c, Database = <Self::Table as Table>::Database> + Send + 'a,
     ) -> impl Future<Output = Result<Self::Table>> + Send + 'a;
 }
 
@@ -0,0 +1,52 @@
+use crate::ddl::SqlType;
+
+/// Static information about a table, implemented by `derive(Table)`.
+///
+/// This can be used to write generic code over tables, e.g for exporting data or audit logging,
+/// without repeating the column lists of every table.
+pub trait TableMeta {
+    /// Schema of the table, if specified using `#[ormx(schema = ..)]`.
+    const SCHEMA: Option<&'static str>;
+    /// Name of the table, without the schema.
+    const TABLE_NAME: &'static str;
+    /// Name of the ID column.
+    const ID_COLUMN: &'static str;
+    /// All columns of the table, in the order of the fields of the struct.
+    const COLUMNS: &'static [ColumnMeta];
+
+    /// Query used by [Table::get](crate::Table::get).
+    const GET_SQL: &'static str;
+    /// Query used by [Table::stream_all](crate::Table::stream_all).
+    const ALL_SQL: &'static str;
+    /// Query used by [Table::stream_all_paginated](crate::Table::stream_all_paginated).
+    const ALL_PAGINATED_SQL: &'static str;
+    /// Query used by [Table::update](crate::Table::update).
+    const UPDATE_SQL: &'static str;
+    /// Query used by [Delete::delete_row](crate::Delete::delete_row).
+    const DELETE_SQL: &'static str;
+
+    /// Returns the column of the given field of the struct.
+    fn column(field: &str) -> Option<&'static ColumnMeta> {
+        Self::COLUMNS.iter().find(|column| column.field == field)
+    }
+}
+
+/// Static information about a column of a table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnMeta {
+    /// Name of the field in the struct.
+    pub field: &'static str,
+    /// Name of the column in the database.
+    pub column: &'static str,
+    /// Whether the field is an `Option<T>`.
+    pub nullable: bool,
+    /// Whether the field is annotated with `#[ormx(custom_type)]`.
+    pub custom_type: bool,
+    /// Whether the value is generated by the database, see `#[ormx(default)]`.
+    pub default: bool,
+    /// Whether the column is never written by [Table::update](crate::Table::update).
+    /// This is only the case for the ID column.
+    pub read_only: bool,
+    /// SQL type of the column, if it is known.
+    pub sql_type: Option<SqlType>,
+}
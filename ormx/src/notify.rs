@@ -0,0 +1,138 @@
+//! Change feeds of tables using `#[ormx(notify)]`, published by a trigger using `pg_notify`.
+//!
+//! Every stream of changes uses its own `PgListener`, which is either supplied by the caller or
+//! connected using a pool. Notifications are only delivered while the listener is connected.
+//! `PgListener` reconnects automatically, but changes made in the meantime are lost.
+
+use std::{fmt::Debug, str::FromStr};
+
+use futures::{stream, Stream};
+use sqlx::{
+    postgres::{PgListener, PgPool},
+    Error, Postgres, Result,
+};
+
+use crate::Table;
+
+/// A change of a row, published by the trigger of a table using `#[ormx(notify)]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T: Table> {
+    /// A row has been inserted.
+    Inserted(T),
+    /// A row has been updated.
+    Updated(T),
+    /// The row with this ID has been deleted.
+    Deleted(T::Id),
+}
+
+impl<T: Table> Change<T> {
+    /// Returns the ID of the changed row.
+    pub fn id(&self) -> T::Id {
+        match self {
+            Change::Inserted(row) | Change::Updated(row) => row.id(),
+            Change::Deleted(id) => *id,
+        }
+    }
+}
+
+/// Listens on `channel` using `listener` and yields its changes.
+/// Inserted and updated rows are queried using the connection of the listener. If such a row has
+/// been deleted before it could be queried, the change is skipped in favor of the following
+/// deletion. Notifications on other channels the listener listens on are ignored.
+#[doc(hidden)]
+pub fn changes<T>(
+    listener: PgListener,
+    channel: &'static str,
+) -> impl Stream<Item = Result<Change<T>>> + Send + 'static
+where
+    T: Table<Database = Postgres>,
+    T::Id: FromStr,
+{
+    // errors are yielded without ending the stream
+    stream::unfold(
+        (listener, false),
+        move |(mut listener, listening)| async move {
+            if !listening {
+                if let Err(err) = listener.listen(channel).await {
+                    return Some((Err(err), (listener, false)));
+                }
+            }
+            let change = next_change(&mut listener, channel).await;
+            Some((change, (listener, true)))
+        },
+    )
+}
+
+/// Connects a listener using `pool`, which then behaves like [changes].
+#[doc(hidden)]
+pub fn changes_from_pool<T>(
+    pool: &PgPool,
+    channel: &'static str,
+) -> impl Stream<Item = Result<Change<T>>> + Send + 'static
+where
+    T: Table<Database = Postgres>,
+    T::Id: FromStr,
+{
+    // errors are yielded without ending the stream
+    stream::unfold(
+        (pool.clone(), None::<PgListener>),
+        move |(pool, listener)| async move {
+            let mut listener = match listener {
+                Some(listener) => listener,
+                None => match listen(&pool, channel).await {
+                    Ok(listener) => listener,
+                    Err(err) => return Some((Err(err), (pool, None))),
+                },
+            };
+            let change = next_change(&mut listener, channel).await;
+            Some((change, (pool, Some(listener))))
+        },
+    )
+}
+
+async fn listen(pool: &PgPool, channel: &str) -> Result<PgListener> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(channel).await?;
+    Ok(listener)
+}
+
+/// Receives the next change published on `channel`.
+async fn next_change<T>(listener: &mut PgListener, channel: &str) -> Result<Change<T>>
+where
+    T: Table<Database = Postgres>,
+    T::Id: FromStr,
+{
+    loop {
+        let notification = listener.recv().await?;
+        if notification.channel() != channel {
+            continue;
+        }
+        let (op, id) = parse_payload::<T>(notification.payload())?;
+        // the cached row, if any, is outdated
+        T::invalidate_cached(id);
+        // the row is read from the listener's connection, so it must not be served from the cache
+        return match op {
+            "DELETE" => Ok(Change::Deleted(id)),
+            op => match T::get_uncached(&mut *listener, id).await {
+                Ok(row) if op == "INSERT" => Ok(Change::Inserted(row)),
+                Ok(row) => Ok(Change::Updated(row)),
+                Err(Error::RowNotFound) => continue,
+                Err(err) => Err(err),
+            },
+        };
+    }
+}
+
+/// Parses a payload like `UPDATE:42`.
+fn parse_payload<T>(payload: &str) -> Result<(&str, T::Id)>
+where
+    T: Table,
+    T::Id: FromStr,
+{
+    let invalid = || Error::Protocol(format!("invalid change notification: {payload:?}"));
+    let (op, id) = payload.split_once(':').ok_or_else(invalid)?;
+    if !matches!(op, "INSERT" | "UPDATE" | "DELETE") {
+        return Err(invalid());
+    }
+    Ok((op, id.parse().map_err(|_| invalid())?))
+}
@@ -49,6 +49,20 @@ mod postgres;
 /// Please note that conditions can't be nested right now.
 /// Also, the number of conditions per query is currently limited to 5.
 ///
+/// # Databases
+/// The placeholders of the arguments depend on the database (`$1`, `$2`, .. for PostgreSQL and
+/// `?` for MySQL/MariaDB). If multiple backends are enabled, the database has to be selected by
+/// prefixing the arguments with `postgres;`, `mysql;` or `mariadb;`:
+/// ```rust,ignore
+/// conditional_query_as!(
+///     mysql;
+///     User,
+///     "SELECT * FROM users WHERE user_id =" ?(user_id)
+/// );
+/// ```
+/// Since the queries are still checked using the `DATABASE_URL` of the crate, queries for
+/// a different database have to be written using the unchecked functions of sqlx instead.
+///
 /// Example:
 /// ```rust,ignore
 /// let limit = Some(10);
@@ -63,11 +77,50 @@ mod postgres;
 ///
 #[macro_export]
 macro_rules! conditional_query_as {
+    ( postgres; $($t:tt)* ) => {
+        $crate::__conditional_query_as_postgres!($($t)*)
+    };
+    ( mysql; $($t:tt)* ) => {
+        $crate::__conditional_query_as_mysql!($($t)*)
+    };
+    ( mariadb; $($t:tt)* ) => {
+        $crate::__conditional_query_as_mysql!($($t)*)
+    };
     ( $($t:tt)* ) => {
         $crate::__conditional_query_as_impl!($($t)*)
     };
 }
 
+// Without a selected database, use the only enabled one.
+#[cfg(all(feature = "postgres", not(any(feature = "mysql", feature = "mariadb"))))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conditional_query_as_impl {
+    ( $($t:tt)* ) => {
+        $crate::__conditional_query_as_postgres!($($t)*)
+    };
+}
+
+#[cfg(all(any(feature = "mysql", feature = "mariadb"), not(feature = "postgres")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conditional_query_as_impl {
+    ( $($t:tt)* ) => {
+        $crate::__conditional_query_as_mysql!($($t)*)
+    };
+}
+
+#[cfg(all(feature = "postgres", any(feature = "mysql", feature = "mariadb")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __conditional_query_as_impl {
+    ( $($t:tt)* ) => {
+        compile_error!(
+            "multiple backends are enabled, select one using `conditional_query_as!(postgres; ..)`, `conditional_query_as!(mysql; ..)` or `conditional_query_as!(mariadb; ..)`"
+        )
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __build_query {
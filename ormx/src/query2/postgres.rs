@@ -1,6 +1,6 @@
 #[doc(hidden)]
 #[macro_export]
-macro_rules! __conditional_query_as_impl {
+macro_rules! __conditional_query_as_postgres {
     ( $($t:tt)* ) => {
         $crate::__build_query!(
             // 100 parameters should hopefully be enough ._.
@@ -0,0 +1,194 @@
+//! Routing of queries to a primary database and its read replicas.
+//!
+//! [ReplicaSet] and [ReadYourWrites] implement `sqlx::Executor`, so they can be passed to every
+//! generated method. Plain `SELECT` queries (`get`, getters, `stream_all*`, counts) are executed on
+//! a replica, while everything else (`insert`, `update`, setters, patches and deletes) is executed
+//! on the primary. Since `insert` takes `sqlx::Acquire`, which they implement as well, connections
+//! acquired from them always belong to the primary.
+
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use futures::{future::BoxFuture, stream::BoxStream};
+use sqlx::{
+    pool::PoolConnection, Acquire, Database, Describe, Either, Execute, Executor, Pool, Result,
+    Transaction,
+};
+
+/// Decides on which pool a query is executed.
+pub trait Router<DB: Database>: Send + Sync + Debug {
+    /// Returns the pool the given query should be executed on.
+    fn route(&self, sql: &str) -> &Pool<DB>;
+}
+
+/// A primary database together with its read replicas.
+/// Reads are distributed across the replicas round-robin.
+#[derive(Debug)]
+pub struct ReplicaSet<DB: Database> {
+    primary: Pool<DB>,
+    replicas: Vec<Pool<DB>>,
+    next: AtomicUsize,
+}
+
+impl<DB: Database> ReplicaSet<DB> {
+    pub fn new(primary: Pool<DB>, replicas: impl IntoIterator<Item = Pool<DB>>) -> Self {
+        Self {
+            primary,
+            replicas: replicas.into_iter().collect(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the pool of the primary.
+    pub fn primary(&self) -> &Pool<DB> {
+        &self.primary
+    }
+
+    /// Returns the pool of the next replica, or the primary if there are no replicas.
+    pub fn replica(&self) -> &Pool<DB> {
+        if self.replicas.is_empty() {
+            return &self.primary;
+        }
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        &self.replicas[idx]
+    }
+
+    /// Starts a scope in which reads go to the primary once something has been written, so that
+    /// the written rows can be read back regardless of replication lag.
+    pub fn read_your_writes(&self) -> ReadYourWrites<'_, DB> {
+        ReadYourWrites {
+            set: self,
+            written: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<DB: Database> Router<DB> for ReplicaSet<DB> {
+    fn route(&self, sql: &str) -> &Pool<DB> {
+        if is_read(sql) {
+            self.replica()
+        } else {
+            &self.primary
+        }
+    }
+}
+
+/// Routes queries like its [ReplicaSet] until the first write, after which every query is
+/// executed on the primary. Created using [ReplicaSet::read_your_writes].
+#[derive(Debug)]
+pub struct ReadYourWrites<'a, DB: Database> {
+    set: &'a ReplicaSet<DB>,
+    written: AtomicBool,
+}
+
+impl<DB: Database> Router<DB> for ReadYourWrites<'_, DB> {
+    fn route(&self, sql: &str) -> &Pool<DB> {
+        if !is_read(sql) {
+            self.written.store(true, Ordering::Relaxed);
+        }
+        if self.written.load(Ordering::Relaxed) {
+            &self.set.primary
+        } else {
+            self.set.replica()
+        }
+    }
+}
+
+/// Returns true if the query is a `SELECT` which does not lock any rows.
+fn is_read(sql: &str) -> bool {
+    const LOCKS: &[&str] = &[
+        "FOR UPDATE",
+        "FOR NO KEY UPDATE",
+        "FOR SHARE",
+        "FOR KEY SHARE",
+        "LOCK IN SHARE MODE",
+    ];
+    let sql = sql.trim_start().to_ascii_uppercase();
+    sql.starts_with("SELECT") && !LOCKS.iter().any(|lock| sql.contains(lock))
+}
+
+macro_rules! impl_executor {
+    ($($t:tt)*) => {
+        impl<'p, DB: Database> Executor<'p> for &'_ $($t)*
+        where
+            for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+        {
+            type Database = DB;
+
+            fn fetch_many<'e, 'q: 'e, E>(
+                self,
+                query: E,
+            ) -> BoxStream<'e, Result<Either<DB::QueryResult, DB::Row>>>
+            where
+                'p: 'e,
+                E: 'q + Execute<'q, DB>,
+            {
+                self.route(query.sql()).fetch_many(query)
+            }
+
+            fn fetch_optional<'e, 'q: 'e, E>(
+                self,
+                query: E,
+            ) -> BoxFuture<'e, Result<Option<DB::Row>>>
+            where
+                'p: 'e,
+                E: 'q + Execute<'q, DB>,
+            {
+                self.route(query.sql()).fetch_optional(query)
+            }
+
+            fn prepare_with<'e, 'q: 'e>(
+                self,
+                sql: &'q str,
+                parameters: &'e [DB::TypeInfo],
+            ) -> BoxFuture<'e, Result<DB::Statement<'q>>>
+            where
+                'p: 'e,
+            {
+                self.route(sql).prepare_with(sql, parameters)
+            }
+
+            #[doc(hidden)]
+            fn describe<'e, 'q: 'e>(self, sql: &'q str) -> BoxFuture<'e, Result<Describe<DB>>>
+            where
+                'p: 'e,
+            {
+                self.route(sql).describe(sql)
+            }
+        }
+    };
+}
+
+impl_executor!(ReplicaSet<DB>);
+impl_executor!(ReadYourWrites<'_, DB>);
+
+impl<'a, DB: Database> Acquire<'a> for &'_ ReplicaSet<DB> {
+    type Database = DB;
+    type Connection = PoolConnection<DB>;
+
+    fn acquire(self) -> BoxFuture<'static, Result<PoolConnection<DB>>> {
+        Box::pin(self.primary.acquire())
+    }
+
+    fn begin(self) -> BoxFuture<'a, Result<Transaction<'a, DB>>> {
+        Acquire::begin(&self.primary)
+    }
+}
+
+impl<'a, DB: Database> Acquire<'a> for &'_ ReadYourWrites<'_, DB> {
+    type Database = DB;
+    type Connection = PoolConnection<DB>;
+
+    fn acquire(self) -> BoxFuture<'static, Result<PoolConnection<DB>>> {
+        // the connection is most likely used to write
+        self.written.store(true, Ordering::Relaxed);
+        self.set.acquire()
+    }
+
+    fn begin(self) -> BoxFuture<'a, Result<Transaction<'a, DB>>> {
+        self.written.store(true, Ordering::Relaxed);
+        self.set.begin()
+    }
+}
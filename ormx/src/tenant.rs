@@ -0,0 +1,64 @@
+//! Helpers for running the generated queries against a schema selected at runtime.
+//!
+//! ormx checks every query at compile time against the tables found on the `search_path` of
+//! `DATABASE_URL` (the template schema). Tables which are not qualified with
+//! `#[ormx(schema = ..)]` are resolved through the `search_path` at runtime as well, so pointing
+//! the `search_path` of a connection to a tenant schema runs all generated CRUD against it.
+
+use sqlx::{Acquire, Error, PgConnection, Postgres, Result, Transaction};
+
+/// Sets the `search_path` of this connection to the given schema.
+///
+/// This persists for the lifetime of the connection, also after it has been returned to a pool.
+/// Prefer [begin_in_schema] when working with a pool.
+pub async fn set_search_path(conn: &mut PgConnection, schema: &str) -> Result<()> {
+    let sql = format!(
+        "SET search_path TO {}",
+        checked_schema(&mut *conn, schema).await?
+    );
+    sqlx::query(&sql).execute(conn).await?;
+    Ok(())
+}
+
+/// Begins a transaction in which the `search_path` is set to the given schema.
+/// Once the transaction ends, the `search_path` of the connection is restored.
+pub async fn begin_in_schema<'c>(
+    db: impl Acquire<'c, Database = Postgres>,
+    schema: &str,
+) -> Result<Transaction<'c, Postgres>> {
+    let mut tx = db.begin().await?;
+    let sql = format!(
+        "SET LOCAL search_path TO {}",
+        checked_schema(&mut tx, schema).await?
+    );
+    sqlx::query(&sql).execute(&mut *tx).await?;
+    Ok(tx)
+}
+
+/// Validates the schema name and checks that it exists, returning the quoted identifier.
+async fn checked_schema(conn: &mut PgConnection, schema: &str) -> Result<String> {
+    let mut chars = schema.chars();
+    let valid = schema.len() <= 63
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if !valid {
+        return Err(Error::Configuration(
+            format!("invalid schema name {schema:?}").into(),
+        ));
+    }
+
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_namespace WHERE nspname = $1)")
+            .bind(schema)
+            .fetch_one(conn)
+            .await?;
+    if !exists {
+        return Err(Error::Configuration(
+            format!("schema {schema:?} does not exist").into(),
+        ));
+    }
+
+    Ok(format!("\"{schema}\""))
+}
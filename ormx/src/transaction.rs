@@ -0,0 +1,275 @@
+//! Running a closure inside a transaction which is retried on serialization failures and
+//! deadlocks.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use futures::future::BoxFuture;
+use sqlx::{Database, Pool, Transaction};
+
+/// Waits for the given duration, e.g using `tokio::time::sleep`.
+pub type Sleep = fn(Duration) -> BoxFuture<'static, ()>;
+
+/// How often and how fast a failed transaction is retried.
+#[derive(Debug, Clone)]
+pub struct Retry {
+    attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    sleep: Option<Sleep>,
+}
+
+impl Default for Retry {
+    /// Tries at most 5 times, waiting between 10ms and 1s (with jitter) in between.
+    /// Waiting requires the `runtime-tokio` feature, or a timer set using [Retry::sleep].
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+            jitter: true,
+            sleep: default_sleep(),
+        }
+    }
+}
+
+#[cfg(feature = "runtime-tokio")]
+fn default_sleep() -> Option<Sleep> {
+    Some(|duration| Box::pin(tokio::time::sleep(duration)))
+}
+
+#[cfg(not(feature = "runtime-tokio"))]
+fn default_sleep() -> Option<Sleep> {
+    None
+}
+
+impl Retry {
+    /// Sets the maximum number of attempts, including the first one.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the time to wait before the first retry, which doubles for every further retry
+    /// until it reaches `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Returns the maximum number of attempts, including the first one.
+    pub fn max_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Enables or disables waiting a random fraction of the backoff instead of the full backoff.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the timer used to wait before retrying, e.g for a runtime other than tokio.
+    /// Without a timer, transactions are retried immediately.
+    pub fn sleep(mut self, sleep: Sleep) -> Self {
+        self.sleep = Some(sleep);
+        self
+    }
+
+    /// Returns how long to wait before retrying after the given attempt (starting at 1) failed,
+    /// or `None` if no attempts remain.
+    pub fn backoff_after(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.attempts {
+            return None;
+        }
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter {
+            let random = RandomState::new().build_hasher().finish();
+            Some(backoff.mul_f64((random % 1000) as f64 / 1000.0))
+        } else {
+            Some(backoff)
+        }
+    }
+}
+
+/// An error returned from a transaction, which may be caused by the database.
+pub trait TransactionError: From<sqlx::Error> {
+    /// Returns the database error this error was caused by, if any.
+    fn as_sqlx(&self) -> Option<&sqlx::Error>;
+}
+
+impl TransactionError for sqlx::Error {
+    fn as_sqlx(&self) -> Option<&sqlx::Error> {
+        Some(self)
+    }
+}
+
+/// Returns true if the transaction failed due to a serialization failure or deadlock, in which case
+/// it can safely be retried.
+/// These are `40001` and `40P01` on PostgreSQL, and the errors `1213` (deadlock) and `1205`
+/// (lock wait timeout) on MySQL.
+pub fn is_retryable(err: &sqlx::Error) -> bool {
+    let sqlx::Error::Database(err) = err else {
+        return false;
+    };
+    #[cfg(any(feature = "mysql", feature = "mariadb"))]
+    if let Some(err) = err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+        return matches!(err.number(), 1213 | 1205);
+    }
+    matches!(err.code().as_deref(), Some("40001" | "40P01"))
+}
+
+/// Runs `f` inside a transaction, using the default [Retry] configuration.
+/// See [transaction_with].
+pub async fn transaction<DB, F, R, E>(pool: &Pool<DB>, f: F) -> Result<R, E>
+where
+    DB: Database,
+    F: for<'t> FnMut(&'t mut Transaction<'static, DB>) -> BoxFuture<'t, Result<R, E>>,
+    E: TransactionError,
+{
+    transaction_with(pool, Retry::default(), f).await
+}
+
+/// Runs `f` inside a transaction. If `f` succeeds, the transaction is committed, otherwise it is
+/// rolled back. If either `f` or the commit failed with an error for which [is_retryable] returns
+/// true, the whole transaction is retried after waiting for the backoff of `retry`.
+///
+/// Since `f` may run multiple times, it should not have side effects outside of the transaction.
+pub async fn transaction_with<DB, F, R, E>(pool: &Pool<DB>, retry: Retry, mut f: F) -> Result<R, E>
+where
+    DB: Database,
+    F: for<'t> FnMut(&'t mut Transaction<'static, DB>) -> BoxFuture<'t, Result<R, E>>,
+    E: TransactionError,
+{
+    let mut attempt = 1;
+    loop {
+        let mut tx = pool.begin().await?;
+        let result = match f(&mut tx).await {
+            Ok(value) => tx.commit().await.map(|_| value).map_err(E::from),
+            Err(err) => {
+                // a failed rollback leaves the original error as the more useful one
+                let _ = tx.rollback().await;
+                Err(err)
+            }
+        };
+
+        let backoff = match &result {
+            Err(err) if err.as_sqlx().is_some_and(is_retryable) => retry.backoff_after(attempt),
+            _ => None,
+        };
+        match (backoff, retry.sleep) {
+            (Some(backoff), Some(sleep)) => sleep(backoff).await,
+            (Some(_), None) => (),
+            (None, _) => return result,
+        }
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, error::Error as StdError, fmt, time::Duration};
+
+    use sqlx::error::{DatabaseError, ErrorKind};
+
+    use super::{is_retryable, Retry};
+
+    fn retry() -> Retry {
+        Retry::default()
+            .attempts(5)
+            .backoff(Duration::from_millis(10), Duration::from_millis(50))
+            .jitter(false)
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        let retry = retry();
+        assert_eq!(retry.backoff_after(1), Some(Duration::from_millis(10)));
+        assert_eq!(retry.backoff_after(2), Some(Duration::from_millis(20)));
+        assert_eq!(retry.backoff_after(3), Some(Duration::from_millis(40)));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let retry = retry().attempts(100);
+        assert_eq!(retry.backoff_after(4), Some(Duration::from_millis(50)));
+        assert_eq!(retry.backoff_after(99), Some(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn backoff_is_exhausted() {
+        let retry = retry();
+        assert!(retry.backoff_after(4).is_some());
+        assert_eq!(retry.backoff_after(5), None);
+        assert_eq!(retry.backoff_after(6), None);
+        assert_eq!(Retry::default().attempts(0).backoff_after(1), None);
+    }
+
+    #[test]
+    fn jitter_stays_below_backoff() {
+        let retry = retry().jitter(true);
+        for attempt in 1..5 {
+            assert!(retry.backoff_after(attempt).unwrap() <= Duration::from_millis(50));
+        }
+    }
+
+    #[derive(Debug)]
+    struct CodeError(&'static str);
+
+    impl fmt::Display for CodeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "error {}", self.0)
+        }
+    }
+
+    impl StdError for CodeError {}
+
+    impl DatabaseError for CodeError {
+        fn message(&self) -> &str {
+            "error"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(CodeError(code)))
+    }
+
+    #[test]
+    fn serialization_failures_and_deadlocks_are_retryable() {
+        assert!(is_retryable(&database_error("40001")));
+        assert!(is_retryable(&database_error("40P01")));
+    }
+
+    #[test]
+    fn other_errors_are_not_retryable() {
+        assert!(!is_retryable(&database_error("23505")));
+        assert!(!is_retryable(&sqlx::Error::RowNotFound));
+    }
+}
@@ -0,0 +1,328 @@
+//! Verification of the database schema at runtime.
+
+use std::{error::Error, fmt};
+
+use futures::future::BoxFuture;
+use sqlx::{Acquire, Database, Row};
+
+use crate::{
+    ddl::SqlType,
+    meta::{ColumnMeta, TableMeta},
+};
+
+/// A database whose schema can be checked by [verify_schema] and [verify_all].
+pub trait Introspect: Database {
+    #[doc(hidden)]
+    /// Queries the name, nullability and data type of all columns of a table.
+    fn columns<'c>(
+        conn: &'c mut Self::Connection,
+        schema: Option<&'static str>,
+        table: &'static str,
+    ) -> BoxFuture<'c, sqlx::Result<Vec<(String, bool, String)>>>;
+}
+
+macro_rules! impl_introspect {
+    ($db:ty, $sql:literal) => {
+        impl Introspect for $db {
+            fn columns<'c>(
+                conn: &'c mut Self::Connection,
+                schema: Option<&'static str>,
+                table: &'static str,
+            ) -> BoxFuture<'c, sqlx::Result<Vec<(String, bool, String)>>> {
+                Box::pin(async move {
+                    let rows = sqlx::query($sql)
+                        .bind(table)
+                        .bind(schema)
+                        .fetch_all(conn)
+                        .await?;
+                    let mut columns = Vec::with_capacity(rows.len());
+                    for row in rows {
+                        let column: String = row.try_get(0)?;
+                        let is_nullable: String = row.try_get(1)?;
+                        let data_type: String = row.try_get(2)?;
+                        columns.push((column, is_nullable == "YES", data_type.to_lowercase()));
+                    }
+                    Ok(columns)
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "postgres")]
+impl_introspect!(
+    sqlx::Postgres,
+    "SELECT column_name::text, is_nullable::text, data_type::text \
+    FROM information_schema.columns \
+    WHERE table_name = $1 AND table_schema = COALESCE($2, current_schema())"
+);
+#[cfg(any(feature = "mysql", feature = "mariadb"))]
+impl_introspect!(
+    sqlx::MySql,
+    "SELECT CAST(column_name AS CHAR), CAST(is_nullable AS CHAR), \
+    CAST(data_type AS CHAR) \
+    FROM information_schema.columns \
+    WHERE table_name = ? AND table_schema = COALESCE(?, DATABASE())"
+);
+
+/// A table registered by `derive(Table)`, used by [verify_all].
+#[doc(hidden)]
+pub struct Registration {
+    database: &'static str,
+    schema: Option<&'static str>,
+    table: &'static str,
+    columns: &'static [ColumnMeta],
+}
+
+impl Registration {
+    pub const fn of<T: TableMeta, DB: Database>() -> Self {
+        Self {
+            database: DB::NAME,
+            schema: T::SCHEMA,
+            table: T::TABLE_NAME,
+            columns: T::COLUMNS,
+        }
+    }
+}
+
+inventory::collect!(Registration);
+
+/// A difference between a table as described by `derive(Table)` and the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The table does not exist.
+    MissingTable { table: String },
+    /// The column does not exist.
+    MissingColumn { table: String, column: String },
+    /// The field is an `Option<T>`, but the column is `NOT NULL`, or vice versa.
+    Nullability {
+        table: String,
+        column: String,
+        nullable_field: bool,
+    },
+    /// The type of the column is not compatible with the type of the field.
+    Type {
+        table: String,
+        column: String,
+        expected: SqlType,
+        found: String,
+    },
+}
+
+/// Error returned by [verify_schema] and [verify_all].
+#[derive(Debug)]
+pub enum SchemaError {
+    /// Querying the schema from the database failed.
+    Database(sqlx::Error),
+    /// The schema of the database differs from the tables.
+    Mismatch(Vec<Mismatch>),
+}
+
+/// Checks that the table exists in the database and that its columns match the struct.
+///
+/// Missing columns, nullability mismatches and incompatible types are reported.
+/// The types of columns using `#[ormx(sql_type = ..)]` or custom types are not checked.
+pub async fn verify_schema<'c, T: TableMeta, DB: Introspect>(
+    db: impl Acquire<'c, Database = DB>,
+) -> Result<(), SchemaError> {
+    let mut conn = db.acquire().await?;
+    let mismatches = verify_table::<DB>(&mut conn, &Registration::of::<T, DB>()).await?;
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Mismatch(mismatches))
+    }
+}
+
+/// Like [verify_schema], but checks every non-generic table deriving `Table` in the program
+/// which uses the same database.
+pub async fn verify_all<'c, DB: Introspect>(
+    db: impl Acquire<'c, Database = DB>,
+) -> Result<(), SchemaError> {
+    let mut conn = db.acquire().await?;
+    let mut mismatches = vec![];
+    for table in inventory::iter::<Registration> {
+        if table.database == DB::NAME {
+            mismatches.extend(verify_table::<DB>(&mut conn, table).await?);
+        }
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError::Mismatch(mismatches))
+    }
+}
+
+async fn verify_table<DB: Introspect>(
+    conn: &mut DB::Connection,
+    table: &Registration,
+) -> sqlx::Result<Vec<Mismatch>> {
+    let found = DB::columns(conn, table.schema, table.table).await?;
+
+    let name = match table.schema {
+        Some(schema) => format!("{schema}.{}", table.table),
+        None => table.table.to_owned(),
+    };
+    if found.is_empty() {
+        return Ok(vec![Mismatch::MissingTable { table: name }]);
+    }
+
+    let mut mismatches = vec![];
+    for expected in table.columns {
+        let Some((_, nullable, data_type)) = found.iter().find(|(c, ..)| c == expected.column)
+        else {
+            mismatches.push(Mismatch::MissingColumn {
+                table: name.clone(),
+                column: expected.column.to_owned(),
+            });
+            continue;
+        };
+
+        if *nullable != expected.nullable {
+            mismatches.push(Mismatch::Nullability {
+                table: name.clone(),
+                column: expected.column.to_owned(),
+                nullable_field: expected.nullable,
+            });
+        }
+
+        if let Some(sql_type) = expected.sql_type {
+            if !sql_type.accepts(data_type) {
+                mismatches.push(Mismatch::Type {
+                    table: name.clone(),
+                    column: expected.column.to_owned(),
+                    expected: sql_type,
+                    found: data_type.clone(),
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+impl SqlType {
+    /// Returns true if a column with the given `information_schema.columns.data_type` can be
+    /// decoded into a field of this type.
+    /// Custom types are never checked.
+    fn accepts(self, data_type: &str) -> bool {
+        use SqlType::*;
+
+        let accepted: &[&str] = match self {
+            Boolean => &["boolean", "tinyint", "bit"],
+            TinyInt => &["tinyint", "smallint"],
+            SmallInt => &["smallint"],
+            Integer => &["integer", "int", "mediumint"],
+            BigInt => &["bigint"],
+            UnsignedTinyInt | UnsignedSmallInt | UnsignedInteger | UnsignedBigInt => &[
+                "tinyint",
+                "smallint",
+                "mediumint",
+                "int",
+                "integer",
+                "bigint",
+                "numeric",
+                "oid",
+            ],
+            Real => &["real", "float"],
+            Double => &["double precision", "double"],
+            Decimal => &["numeric", "decimal"],
+            Text => &[
+                "text",
+                "character varying",
+                "varchar",
+                "character",
+                "char",
+                "bpchar",
+                "name",
+                "citext",
+                "tinytext",
+                "mediumtext",
+                "longtext",
+                "enum",
+            ],
+            Bytes => &[
+                "bytea",
+                "blob",
+                "tinyblob",
+                "mediumblob",
+                "longblob",
+                "binary",
+                "varbinary",
+            ],
+            Uuid => &["uuid", "binary", "char"],
+            Json => &["json", "jsonb"],
+            Date => &["date"],
+            Time => &["time", "time without time zone", "time with time zone"],
+            Timestamp => &["timestamp without time zone", "datetime", "timestamp"],
+            TimestampTz => &["timestamp with time zone", "timestamp"],
+            Custom(_) => return true,
+        };
+        accepted.contains(&data_type)
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::MissingTable { table } => write!(f, "table {table} does not exist"),
+            Mismatch::MissingColumn { table, column } => {
+                write!(f, "column {table}.{column} does not exist")
+            }
+            Mismatch::Nullability {
+                table,
+                column,
+                nullable_field: true,
+            } => write!(
+                f,
+                "column {table}.{column} is NOT NULL, but the field is optional"
+            ),
+            Mismatch::Nullability {
+                table,
+                column,
+                nullable_field: false,
+            } => write!(
+                f,
+                "column {table}.{column} is nullable, but the field is not optional"
+            ),
+            Mismatch::Type {
+                table,
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "column {table}.{column} has type {found}, but the field expects {expected:?}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Database(err) => write!(f, "could not query the schema: {err}"),
+            SchemaError::Mismatch(mismatches) => {
+                write!(f, "the database schema does not match:")?;
+                for mismatch in mismatches {
+                    write!(f, "\n- {mismatch}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for SchemaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SchemaError::Database(err) => Some(err),
+            SchemaError::Mismatch(_) => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for SchemaError {
+    fn from(err: sqlx::Error) -> Self {
+        SchemaError::Database(err)
+    }
+}
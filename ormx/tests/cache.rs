@@ -0,0 +1,129 @@
+//! Requires a PostgreSQL database, which is selected using `DATABASE_URL`.
+//! The cache is shared by all tests, so each of them uses its own row.
+#![cfg(feature = "postgres")]
+
+use std::time::Duration;
+
+use ormx::{Cached, Table};
+use sqlx::{Connection, PgConnection, PgPool};
+
+#[derive(Debug, Clone, ormx::Table)]
+#[ormx(table = "cache_test", id = id, unchecked, cache)]
+struct Counter {
+    id: i32,
+    #[ormx(counter, set)]
+    hits: i32,
+}
+
+/// Uses a regular table, since only rows read using a pool are cached.
+#[derive(Debug, Clone, ormx::Table)]
+#[ormx(table = "cache_pool_test", id = id, unchecked, cache)]
+struct Pooled {
+    id: i32,
+    #[ormx(set)]
+    hits: i32,
+}
+
+async fn connect() -> Option<PgConnection> {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return None;
+    };
+    let mut conn = PgConnection::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "CREATE TEMPORARY TABLE cache_test (id integer PRIMARY KEY, hits integer NOT NULL);
+         INSERT INTO cache_test VALUES (1, 0), (2, 0), (3, 0);",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+    Some(conn)
+}
+
+#[tokio::test]
+async fn rolled_back_writes_are_not_cached() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    let mut tx = conn.begin().await.unwrap();
+    let mut row = Counter::get(&mut *tx, 1).await.unwrap();
+    row.increment_hits(&mut *tx, 5).await.unwrap();
+    assert_eq!(row.hits, 5);
+    row.set_hits(&mut *tx, 7).await.unwrap();
+    row.hits = 9;
+    row.update(&mut *tx).await.unwrap();
+    tx.rollback().await.unwrap();
+
+    assert_eq!(Counter::get(&mut conn, 1).await.unwrap().hits, 0);
+}
+
+#[tokio::test]
+async fn committed_writes_are_visible() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    let mut row = Counter::get(&mut conn, 2).await.unwrap();
+    let mut tx = conn.begin().await.unwrap();
+    row.increment_hits(&mut *tx, 5).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(Counter::get(&mut conn, 2).await.unwrap().hits, 5);
+}
+
+#[tokio::test]
+async fn rolled_back_reads_are_not_cached() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    let mut tx = conn.begin().await.unwrap();
+    let mut row = Counter::get(&mut *tx, 3).await.unwrap();
+    row.set_hits(&mut *tx, 7).await.unwrap();
+    assert_eq!(Counter::get(&mut *tx, 3).await.unwrap().hits, 7);
+    tx.rollback().await.unwrap();
+
+    assert_eq!(Counter::get(&mut conn, 3).await.unwrap().hits, 0);
+}
+
+#[tokio::test]
+async fn reads_during_a_write_are_not_cached() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "DROP TABLE IF EXISTS cache_pool_test;
+         CREATE TABLE cache_pool_test (id integer PRIMARY KEY, hits integer NOT NULL);
+         INSERT INTO cache_pool_test VALUES (1, 0);",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    Pooled::set_write_hold(Duration::from_millis(200));
+
+    let mut row = Pooled::get(&pool, 1).await.unwrap();
+    let mut tx = pool.begin().await.unwrap();
+    row.set_hits(&mut *tx, 5).await.unwrap();
+    assert_eq!(Pooled::get(&mut *tx, 1).await.unwrap().hits, 5);
+    // the previous state must not be cached, since it's outdated once the write is committed
+    assert_eq!(Pooled::get(&pool, 1).await.unwrap().hits, 0);
+    tx.commit().await.unwrap();
+    assert_eq!(Pooled::get(&pool, 1).await.unwrap().hits, 5);
+
+    // once the write hold expired, pool reads are cached again
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(Pooled::get(&pool, 1).await.unwrap().hits, 5);
+    sqlx::query("UPDATE cache_pool_test SET hits = 9")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(Pooled::get(&pool, 1).await.unwrap().hits, 5);
+
+    sqlx::raw_sql("DROP TABLE cache_pool_test")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
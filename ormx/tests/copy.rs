@@ -0,0 +1,76 @@
+//! Bulk loading of custom types using `copy_in`.
+//! Requires a PostgreSQL database, which is selected using `DATABASE_URL`.
+#![cfg(feature = "postgres")]
+
+use ormx::Table;
+use sqlx::{Connection, PgConnection};
+
+#[derive(Debug, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "copy_mood", rename_all = "lowercase")]
+enum Mood {
+    Happy,
+    Sad,
+}
+
+#[derive(Debug, Clone, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "copy_spot")]
+struct Spot {
+    x: i32,
+    mood: Mood,
+}
+
+#[derive(Debug, ormx::Table)]
+#[ormx(table = "copy_test", id = id, insertable, unchecked)]
+struct Row {
+    id: i32,
+    #[ormx(custom_type)]
+    mood: Mood,
+    #[ormx(custom_type)]
+    moods: Vec<Mood>,
+    #[ormx(custom_type)]
+    spot: Spot,
+    #[ormx(custom_type)]
+    spots: Vec<Spot>,
+}
+
+#[tokio::test]
+async fn copies_custom_types() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let mut conn = PgConnection::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "CREATE TYPE pg_temp.copy_mood AS ENUM ('happy', 'sad');
+         CREATE TYPE pg_temp.copy_spot AS (x integer, mood copy_mood);
+         CREATE TEMPORARY TABLE copy_test (
+             id integer PRIMARY KEY,
+             mood copy_mood NOT NULL,
+             moods copy_mood[] NOT NULL,
+             spot copy_spot NOT NULL,
+             spots copy_spot[] NOT NULL
+         );",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+
+    let spot = Spot {
+        x: 1,
+        mood: Mood::Sad,
+    };
+    let rows = (0..3).map(|id| InsertRow {
+        id,
+        mood: Mood::Happy,
+        moods: vec![Mood::Happy, Mood::Sad],
+        spot: spot.clone(),
+        spots: vec![spot.clone(), spot.clone()],
+    });
+    assert_eq!(InsertRow::copy_in(&mut conn, rows).await.unwrap(), 3);
+
+    let row = Row::get(&mut conn, 2).await.unwrap();
+    assert_eq!(row.mood, Mood::Happy);
+    assert_eq!(row.moods, [Mood::Happy, Mood::Sad]);
+    assert_eq!(row.spot, spot);
+    assert_eq!(row.spots, [spot.clone(), spot]);
+}
@@ -0,0 +1,61 @@
+#![cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+
+use ormx::Dialect;
+
+#[derive(ormx::Table)]
+#[ormx(table = "counters", id = id, insertable, ddl, unchecked)]
+#[allow(dead_code)]
+struct Counter {
+    #[ormx(default)]
+    id: i32,
+    #[ormx(unique, index)]
+    name: String,
+    #[ormx(default, counter, sql_default = "0")]
+    hits: i64,
+    #[ormx(default)]
+    last_hit: Option<i64>,
+    #[ormx(references = "teams(id)")]
+    team: Option<i32>,
+}
+
+#[test]
+fn postgres() {
+    assert_eq!(
+        Counter::create_table_sql(Dialect::Postgres),
+        r#"CREATE TABLE "counters"
+(
+    "id" SERIAL NOT NULL PRIMARY KEY,
+    "name" TEXT NOT NULL UNIQUE,
+    "hits" BIGINT NOT NULL DEFAULT 0,
+    "last_hit" BIGINT,
+    "team" INTEGER REFERENCES teams(id)
+);
+CREATE INDEX "counters_name_idx" ON "counters" ("name");
+"#
+    );
+}
+
+#[test]
+fn mysql() {
+    assert_eq!(
+        Counter::create_table_sql(Dialect::MySql),
+        r#"CREATE TABLE `counters`
+(
+    `id` INT NOT NULL AUTO_INCREMENT PRIMARY KEY,
+    `name` VARCHAR(255) NOT NULL UNIQUE,
+    `hits` BIGINT NOT NULL DEFAULT 0,
+    `last_hit` BIGINT,
+    `team` INT REFERENCES teams(id)
+);
+CREATE INDEX `counters_name_idx` ON `counters` (`name`);
+"#
+    );
+}
+
+#[test]
+fn mariadb() {
+    assert_eq!(
+        Counter::create_table_sql(Dialect::MariaDb),
+        Counter::create_table_sql(Dialect::MySql)
+    );
+}
@@ -0,0 +1,77 @@
+//! Generic tables. The runtime test requires a PostgreSQL database, which is selected using
+//! `DATABASE_URL`.
+#![cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::types::Json;
+
+/// Marks the payload of a row, without being stored in the database.
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Signed;
+
+#[derive(Debug, ormx::Table)]
+#[ormx(table = "generic_test", id = id, insertable, unchecked)]
+#[allow(dead_code)]
+struct Audited<T, M>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin + 'static,
+    M: Send + Sync + Unpin + 'static,
+{
+    #[ormx(default)]
+    id: i32,
+    #[ormx(by_ref, get_many(&str))]
+    author: String,
+    #[ormx(by_ref)]
+    payload: Json<T>,
+    #[ormx(skip)]
+    marker: PhantomData<M>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[allow(dead_code)]
+struct Comment {
+    text: String,
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn insert_and_get() {
+    use ormx::{Insert, Table};
+    use sqlx::{Connection, PgConnection};
+
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let mut conn = PgConnection::connect(&url).await.unwrap();
+    sqlx::query(
+        "CREATE TEMPORARY TABLE generic_test \
+         (id serial PRIMARY KEY, author text NOT NULL, payload jsonb NOT NULL)",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+
+    let inserted = InsertAudited::<Comment, Signed> {
+        author: "alice".to_owned(),
+        payload: Json(Comment {
+            text: "hello".to_owned(),
+        }),
+        marker: PhantomData,
+    }
+    .insert(&mut conn)
+    .await
+    .unwrap();
+
+    let row = Audited::<Comment, Signed>::get(&mut conn, inserted.id)
+        .await
+        .unwrap();
+    assert_eq!(row.payload.0.text, "hello");
+    let rows = Audited::<Comment, Signed>::by_author(&mut conn, "alice")
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+}
@@ -0,0 +1,75 @@
+//! Tables with a non-integer ID on MySQL, which does not support `RETURNING`.
+//! Requires a MySQL database, which is selected using `MYSQL_DATABASE_URL`.
+#![cfg(feature = "mysql")]
+
+use ormx::{Insert, Table};
+use sqlx::{types::Uuid, Connection, MySqlConnection};
+
+/// The UUID is generated before inserting the row.
+#[derive(Debug, ormx::Table)]
+#[ormx(table = "generated_test", id = id, insertable, unchecked)]
+struct Generated {
+    #[ormx(default, generate = "UUID_TO_BIN(UUID())")]
+    id: Uuid,
+    name: String,
+}
+
+/// The UUID is generated by the database, and looked up by a unique field afterwards.
+#[derive(Debug, ormx::Table)]
+#[ormx(table = "lookup_test", id = id, insertable, unchecked)]
+struct LookedUp {
+    #[ormx(default, lookup = email)]
+    id: Uuid,
+    email: String,
+}
+
+async fn connect() -> Option<MySqlConnection> {
+    let Ok(url) = std::env::var("MYSQL_DATABASE_URL") else {
+        eprintln!("MYSQL_DATABASE_URL is not set, skipping");
+        return None;
+    };
+    let mut conn = MySqlConnection::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "CREATE TEMPORARY TABLE generated_test (id BINARY(16) PRIMARY KEY, name TEXT NOT NULL);
+         CREATE TEMPORARY TABLE lookup_test
+         (id BINARY(16) PRIMARY KEY DEFAULT (UUID_TO_BIN(UUID())), email VARCHAR(255) UNIQUE);",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+    Some(conn)
+}
+
+#[tokio::test]
+async fn generated_uuid() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    let inserted = InsertGenerated {
+        name: "a".to_owned(),
+    }
+    .insert(&mut conn)
+    .await
+    .unwrap();
+    assert!(!inserted.id.is_nil());
+    let row = Generated::get(&mut conn, inserted.id).await.unwrap();
+    assert_eq!(row.name, "a");
+}
+
+#[tokio::test]
+async fn looked_up_uuid() {
+    let Some(mut conn) = connect().await else {
+        return;
+    };
+
+    let inserted = InsertLookedUp {
+        email: "a@example.com".to_owned(),
+    }
+    .insert(&mut conn)
+    .await
+    .unwrap();
+    assert!(!inserted.id.is_nil());
+    let row = LookedUp::get(&mut conn, inserted.id).await.unwrap();
+    assert_eq!(row.email, "a@example.com");
+}
@@ -0,0 +1,70 @@
+//! Change feeds over a caller-supplied listener.
+//! Requires a PostgreSQL database, which is selected using `DATABASE_URL`.
+#![cfg(feature = "postgres")]
+
+use futures::StreamExt;
+use ormx::Change;
+use sqlx::{postgres::PgListener, PgPool};
+
+#[derive(Debug, Clone, PartialEq, ormx::Table)]
+#[ormx(table = "notify_test", id = id, unchecked, notify)]
+struct Item {
+    id: i32,
+    name: String,
+}
+
+#[tokio::test]
+async fn streams_changes_of_a_listener() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    sqlx::raw_sql(
+        "DROP TABLE IF EXISTS notify_test;
+         CREATE TABLE notify_test (id integer PRIMARY KEY, name text NOT NULL);",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::raw_sql(Item::notify_trigger_sql())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut listener = PgListener::connect_with(&pool).await.unwrap();
+    // notifications on other channels are ignored
+    listener.listen("notify_test_other").await.unwrap();
+    let mut changes = Box::pin(Item::changes(listener));
+    // the listener starts listening once the stream is polled
+    let next = tokio::spawn(async move {
+        let change = changes.next().await.unwrap().unwrap();
+        (change, changes)
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    sqlx::raw_sql(
+        "SELECT pg_notify('notify_test_other', 'DELETE:2');
+         INSERT INTO notify_test VALUES (1, 'a');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (change, mut changes) = next.await.unwrap();
+    let row = Item {
+        id: 1,
+        name: "a".to_owned(),
+    };
+    assert_eq!(change, Change::Inserted(row));
+
+    sqlx::query("DELETE FROM notify_test")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(changes.next().await.unwrap().unwrap(), Change::Deleted(1));
+
+    sqlx::raw_sql("DROP TABLE notify_test; DROP FUNCTION notify_test_notify();")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
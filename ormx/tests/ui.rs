@@ -0,0 +1,7 @@
+#![cfg(any(feature = "mysql", feature = "postgres", feature = "mariadb"))]
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
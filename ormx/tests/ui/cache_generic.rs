@@ -0,0 +1,9 @@
+#[derive(Clone, ormx::Table)]
+#[ormx(table = "users", id = id, cache)]
+struct User<T> {
+    id: i32,
+    #[ormx(custom_type)]
+    data: T,
+}
+
+fn main() {}
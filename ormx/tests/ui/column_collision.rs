@@ -0,0 +1,9 @@
+#[derive(ormx::Table)]
+#[ormx(table = "measurements", id = id)]
+struct Measurement {
+    id: i32,
+    foo_1: i32,
+    foo1: i32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id)]
+struct User {
+    id: i32,
+    #[ormx(unique)]
+    email: String,
+}
+
+fn main() {}
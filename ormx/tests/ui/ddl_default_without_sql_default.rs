@@ -0,0 +1,10 @@
+#[derive(ormx::Table)]
+#[ormx(table = "counters", id = id, insertable, ddl, unchecked)]
+struct Counter {
+    #[ormx(default)]
+    id: i32,
+    #[ormx(default, counter)]
+    hits: i64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id, table = "accounts")]
+struct User {
+    id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id)]
+enum User {
+    A,
+    B,
+}
+
+fn main() {}
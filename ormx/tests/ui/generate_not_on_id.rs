@@ -0,0 +1,10 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id, insertable)]
+struct User {
+    #[ormx(default)]
+    id: i32,
+    #[ormx(generate = "UUID()")]
+    token: String,
+}
+
+fn main() {}
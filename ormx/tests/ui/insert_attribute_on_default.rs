@@ -0,0 +1,8 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id, insertable)]
+struct User {
+    #[ormx(default, insert_attribute = #[allow(unused)])]
+    id: i32,
+}
+
+fn main() {}
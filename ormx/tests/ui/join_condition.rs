@@ -0,0 +1,36 @@
+#[derive(ormx::Table)]
+#[ormx(table = "teams", id = id, unchecked)]
+struct Team {
+    id: i32,
+    name: String,
+}
+
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id, unchecked)]
+struct User {
+    id: i32,
+    team_id: i32,
+}
+
+#[derive(ormx::Joined)]
+#[ormx(from = User, join(Team, on = "team_id >= id"))]
+struct Compared {
+    team_id: i32,
+    name: String,
+}
+
+#[derive(ormx::Joined)]
+#[ormx(from = User, join(Team, on = "team_id = id AND name = 'a'"))]
+struct Combined {
+    team_id: i32,
+    name: String,
+}
+
+#[derive(ormx::Joined)]
+#[ormx(from = User, join(Team, on = "team = id"))]
+struct Unknown {
+    team_id: i32,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users")]
+struct User {
+    id: i32,
+}
+
+fn main() {}
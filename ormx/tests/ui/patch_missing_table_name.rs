@@ -0,0 +1,9 @@
+struct User;
+
+#[derive(ormx::Patch)]
+#[ormx(table = User, id = "id")]
+struct UpdateUser {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+struct User;
+
+#[derive(ormx::Patch)]
+#[ormx(table_name = "users", table = User, id = "id")]
+struct UpdateUser {
+    #[ormx(colum = "first_name")]
+    name: String,
+}
+
+fn main() {}
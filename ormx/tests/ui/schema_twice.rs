@@ -0,0 +1,7 @@
+#[derive(ormx::Table)]
+#[ormx(table = "auth.users", schema = "auth", id = id)]
+struct User {
+    id: i32,
+}
+
+fn main() {}
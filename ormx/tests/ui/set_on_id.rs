@@ -0,0 +1,8 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id)]
+struct User {
+    #[ormx(set)]
+    id: i32,
+}
+
+fn main() {}
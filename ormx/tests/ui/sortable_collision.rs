@@ -0,0 +1,11 @@
+#[derive(ormx::Table)]
+#[ormx(table = "measurements", id = id)]
+struct Measurement {
+    id: i32,
+    #[ormx(sortable)]
+    foo_1: i32,
+    #[ormx(sortable)]
+    foo1: i32,
+}
+
+fn main() {}
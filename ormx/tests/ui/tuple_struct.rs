@@ -0,0 +1,5 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id)]
+struct User(i32, String);
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(ormx::Table)]
+#[ormx(tabel = "users", id = id)]
+struct User {
+    id: i32,
+}
+
+fn main() {}
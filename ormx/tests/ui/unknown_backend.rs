@@ -0,0 +1,7 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id, backend = postgers)]
+struct User {
+    id: i32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[derive(ormx::Table)]
+#[ormx(table = "users", id = id)]
+struct User {
+    id: i32,
+    #[ormx(get_onee)]
+    email: String,
+    #[ormx(foo)]
+    name: String,
+}
+
+fn main() {}
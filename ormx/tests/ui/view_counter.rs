@@ -0,0 +1,9 @@
+#[derive(ormx::View)]
+#[ormx(view = "active_users")]
+struct ActiveUser {
+    id: i32,
+    #[ormx(counter)]
+    logins: i32,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[derive(ormx::View)]
+struct ActiveUser {
+    id: i32,
+}
+
+fn main() {}